@@ -0,0 +1,139 @@
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{
+    parse_macro_input, Data, DeriveInput, Error, Field, Fields, GenericArgument, LitStr,
+    PathArguments, Type,
+};
+
+/// Derives `fancy_surreal::Databasable`.
+///
+/// The id is read from the field named `id` or from the field marked with
+/// `#[databasable(id)]`. `#[databasable(table = "...")]` on the struct sets
+/// the table used when the `Client` has none.
+#[proc_macro_derive(Databasable, attributes(databasable))]
+pub fn derive_databasable(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> Result<proc_macro2::TokenStream, Error> {
+    let mut table: Option<LitStr> = None;
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("databasable"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("table") {
+                table = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("expected `table = \"...\"`"))
+            }
+        })?;
+    }
+
+    let field = id_field(&input)?;
+    let id = field.ident.clone().unwrap();
+    let key = option_inner(&field.ty)?;
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let table = table.map(|table| {
+        quote! {
+            fn table() -> Option<&'static str> {
+                Some(#table)
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::fancy_surreal::Databasable for #name #ty_generics #where_clause {
+            type Id = #key;
+
+            fn get_id(&self) -> Option<Self::Id> {
+                self.#id.clone()
+            }
+
+            fn set_id(&mut self, id: Option<Self::Id>) {
+                self.#id = id;
+            }
+
+            #table
+        }
+    })
+}
+
+fn id_field(input: &DeriveInput) -> Result<&Field, Error> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "Databasable can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "Databasable can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut marked = None;
+    for field in fields {
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("databasable"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("id") {
+                    if marked.is_some() {
+                        return Err(meta.error("only one field can be marked as id"));
+                    }
+                    marked = Some(field);
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `id`"))
+                }
+            })?;
+        }
+    }
+
+    marked
+        .or_else(|| {
+            fields
+                .iter()
+                .find(|field| field.ident.as_ref().is_some_and(|ident| ident == "id"))
+        })
+        .ok_or_else(|| {
+            Error::new(
+                Span::call_site(),
+                "Databasable needs a field named `id` or one marked with `#[databasable(id)]`",
+            )
+        })
+}
+
+fn option_inner(ty: &Type) -> Result<&Type, Error> {
+    if let Type::Path(path) = ty {
+        if let Some(segment) = path.path.segments.last() {
+            if segment.ident == "Option" {
+                if let PathArguments::AngleBracketed(arguments) = &segment.arguments {
+                    if let Some(GenericArgument::Type(inner)) = arguments.args.first() {
+                        return Ok(inner);
+                    }
+                }
+            }
+        }
+    }
+    Err(Error::new_spanned(
+        ty,
+        "the id field must be an `Option<_>`",
+    ))
+}
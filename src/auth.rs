@@ -0,0 +1,164 @@
+use crate::Error;
+
+use serde::Serialize;
+use std::fmt;
+use surrealdb::engine::any::Any;
+use surrealdb::opt::auth::{Database, Jwt, Namespace, Record, Root};
+use surrealdb::Surreal;
+
+/// How a `Client` authenticates once connected.
+#[derive(Clone)]
+pub enum Credentials {
+    Root {
+        username: String,
+        password: String,
+    },
+    /// A user defined on the namespace the client uses.
+    Namespace {
+        username: String,
+        password: String,
+    },
+    /// A user defined on the database the client uses.
+    Database {
+        username: String,
+        password: String,
+    },
+    /// Signs in through a `DEFINE ACCESS ... TYPE RECORD` with `params`.
+    RecordSignin {
+        access: String,
+        params: serde_json::Value,
+    },
+    /// Signs up through a `DEFINE ACCESS ... TYPE RECORD` with `params`.
+    RecordSignup {
+        access: String,
+        params: serde_json::Value,
+    },
+    /// Reuses a token issued before, e.g. to a frontend. The client acts as
+    /// the token's record user, tokens of system users give no principal.
+    Token(Jwt),
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Root { username, .. } => write!(f, "Root({})", username),
+            Self::Namespace { username, .. } => write!(f, "Namespace({})", username),
+            Self::Database { username, .. } => write!(f, "Database({})", username),
+            Self::RecordSignin { access, .. } => write!(f, "RecordSignin({})", access),
+            Self::RecordSignup { access, .. } => write!(f, "RecordSignup({})", access),
+            Self::Token(_) => write!(f, "Token(REDACTED)"),
+        }
+    }
+}
+
+impl Credentials {
+    pub fn root(username: &str, password: &str) -> Self {
+        Self::Root {
+            username: username.to_string(),
+            password: password.to_string(),
+        }
+    }
+
+    pub fn namespace(username: &str, password: &str) -> Self {
+        Self::Namespace {
+            username: username.to_string(),
+            password: password.to_string(),
+        }
+    }
+
+    pub fn database(username: &str, password: &str) -> Self {
+        Self::Database {
+            username: username.to_string(),
+            password: password.to_string(),
+        }
+    }
+
+    pub fn record_signin<P: Serialize>(access: &str, params: P) -> Result<Self, Error> {
+        Ok(Self::RecordSignin {
+            access: access.to_string(),
+            params: serde_json::to_value(params)?,
+        })
+    }
+
+    pub fn record_signup<P: Serialize>(access: &str, params: P) -> Result<Self, Error> {
+        Ok(Self::RecordSignup {
+            access: access.to_string(),
+            params: serde_json::to_value(params)?,
+        })
+    }
+
+    pub fn token(token: impl Into<Jwt>) -> Self {
+        Self::Token(token.into())
+    }
+
+    /// Credentials to sign in again with, a record user signed up before
+    /// signs in with the same params.
+    pub(crate) fn into_signin(self) -> Self {
+        match self {
+            Self::RecordSignup { access, params } => Self::RecordSignin { access, params },
+            credentials => credentials,
+        }
+    }
+
+    /// Whether the session may belong to a record user afterwards.
+    pub(crate) fn is_record(&self) -> bool {
+        matches!(
+            self,
+            Self::RecordSignin { .. } | Self::RecordSignup { .. } | Self::Token(_)
+        )
+    }
+
+    pub(crate) async fn authenticate(
+        &self,
+        client: &Surreal<Any>,
+        namespace: &str,
+        database: &str,
+    ) -> Result<Jwt, Error> {
+        Ok(match self {
+            Self::Root { username, password } => client.signin(Root { username, password }).await?,
+            Self::Namespace { username, password } => {
+                client
+                    .signin(Namespace {
+                        namespace,
+                        username,
+                        password,
+                    })
+                    .await?
+            }
+            Self::Database { username, password } => {
+                client
+                    .signin(Database {
+                        namespace,
+                        database,
+                        username,
+                        password,
+                    })
+                    .await?
+            }
+            Self::RecordSignin { access, params } => {
+                client
+                    .signin(Record {
+                        namespace,
+                        database,
+                        access,
+                        params: params.clone(),
+                    })
+                    .await?
+            }
+            Self::RecordSignup { access, params } => {
+                client
+                    .signup(Record {
+                        namespace,
+                        database,
+                        access,
+                        params: params.clone(),
+                    })
+                    .await?
+            }
+            Self::Token(token) => {
+                client.authenticate(token.clone()).await?;
+                token.clone()
+            }
+        })
+    }
+}
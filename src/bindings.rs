@@ -0,0 +1,92 @@
+use crate::Error;
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use surrealdb::sql::{to_value, Value};
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(transparent)]
+pub struct Bindings(BTreeMap<String, Value>);
+
+impl Bindings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn bind<T: Into<Value>>(&mut self, name: &str, value: T) -> String {
+        let name = self.free_name(name);
+        self.0.insert(name.clone(), value.into());
+        format!("${}", name)
+    }
+
+    pub fn bind_serialize<T: Serialize + 'static>(
+        &mut self,
+        name: &str,
+        value: T,
+    ) -> Result<String, Error> {
+        let value = to_value(value).map_err(surrealdb::Error::from)?;
+        Ok(self.bind(name, value))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.0.get(name.trim_start_matches('$'))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Replaces the parameters in `query` with their values as literals, for
+    /// `LIVE SELECT`, whose condition is evaluated for every notification
+    /// without the query parameters. Values are written by SurrealDB's own
+    /// formatter, strings and escaped identifiers in `query` are kept as is.
+    pub(crate) fn inline(&self, query: &str) -> String {
+        let mut result = String::with_capacity(query.len());
+        let mut chars = query.char_indices().peekable();
+        while let Some((start, c)) = chars.next() {
+            match c {
+                '\'' | '"' | '`' | '⟨' => {
+                    let close = if c == '⟨' { '⟩' } else { c };
+                    let mut end = query.len();
+                    while let Some((index, c)) = chars.next() {
+                        if c == '\\' {
+                            chars.next();
+                        } else if c == close {
+                            end = index + c.len_utf8();
+                            break;
+                        }
+                    }
+                    result.push_str(&query[start..end]);
+                }
+                '$' => {
+                    let mut end = start + 1;
+                    while let Some((index, c)) =
+                        chars.next_if(|(_, c)| c.is_ascii_alphanumeric() || *c == '_')
+                    {
+                        end = index + c.len_utf8();
+                    }
+                    match self.0.get(&query[start + 1..end]) {
+                        Some(value) => result.push_str(&value.to_string()),
+                        None => result.push_str(&query[start..end]),
+                    }
+                }
+                c => result.push(c),
+            }
+        }
+        result
+    }
+
+    fn free_name(&self, name: &str) -> String {
+        if !self.0.contains_key(name) {
+            return name.to_string();
+        }
+        (1..)
+            .map(|index| format!("{}{}", name, index))
+            .find(|candidate| !self.0.contains_key(candidate))
+            .unwrap()
+    }
+}
@@ -1,8 +1,12 @@
-use crate::{Databasable, Error, Record};
+use crate::condition::validate_field;
+use crate::error::aborted;
+use crate::hierarchy::owner_condition;
+use crate::history::history_str;
+use crate::{Bindings, Condition, Databasable, Error, Record};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
-use serde_json;
-use surrealdb::engine::remote::ws::Client;
+use surrealdb::engine::any::Any;
+use surrealdb::sql::{Id, Thing};
 use surrealdb::Surreal;
 
 pub enum Operator {
@@ -22,41 +26,53 @@ impl Operator {
 }
 
 pub struct Change<'a> {
-    client: Surreal<Client>,
+    client: Surreal<Any>,
     table: &'a str,
+    bindings: Bindings,
     condition: String,
     update: String,
+    history: Option<String>,
+    exclude_deleted: bool,
+    invalid: Option<String>,
 }
 
 impl<'a> Change<'a> {
-    pub fn new(client: Surreal<Client>, table: &'a str, owners: Vec<String>) -> Self {
-        let change = Self {
+    pub fn new(client: Surreal<Any>, table: &'a str, owners: Vec<String>) -> Self {
+        Self::scoped(client, table, owners, false)
+    }
+
+    pub(crate) fn scoped(
+        client: Surreal<Any>,
+        table: &'a str,
+        owners: Vec<String>,
+        hierarchical: bool,
+    ) -> Self {
+        let mut change = Self {
             client,
             table,
+            bindings: Bindings::new(),
             condition: String::new(),
             update: String::new(),
+            history: None,
+            exclude_deleted: false,
+            invalid: None,
         };
 
-        if owners.contains(&"admin".to_string()) {
-            change
-        } else if owners.len() > 1 {
-            change.condition(&format!(
-                "owner in [{}]",
-                owners
-                    .iter()
-                    .map(|owner| format!("\"{}\"", owner))
-                    .collect::<Vec<String>>()
-                    .join(",")
-            ))
-        } else if owners.len() > 0 {
-            change.condition(&format!("owner == \"{}\"", owners[0]))
-        } else {
-            change
+        if owners.is_empty() {
+            return change;
         }
+        let condition = owner_condition(&mut change.bindings, owners, &["editors"], hierarchical);
+        change.and(&condition)
     }
 
-    pub fn condition(mut self, condition: &str) -> Self {
-        if self.condition.len() == 0 {
+    /// Adds the raw `condition`, grouped so it cannot widen the others.
+    pub fn condition(self, condition: &str) -> Self {
+        self.and(&format!("({})", condition))
+    }
+
+    /// Adds `condition`, which must not contain a top level `OR`.
+    fn and(mut self, condition: &str) -> Self {
+        if self.condition.is_empty() {
             self.condition = condition.into();
         } else {
             self.condition = format!("{} AND {}", self.condition, condition);
@@ -69,44 +85,121 @@ impl<'a> Change<'a> {
         key: &str,
         operator: &Operator,
         value: &T,
-    ) -> Result<Self, serde_json::Error> {
-        let separator = if self.update.len() > 0 { ", " } else { "" };
+    ) -> Result<Self, Error> {
+        validate_field(key)?;
+        // The version, timestamps and access fields are maintained by the
+        // client itself.
+        if key != "content" && !key.starts_with("content.") {
+            return Err(Error::validation(&format!(
+                "only content fields can be changed, not {:?}",
+                key
+            )));
+        }
+        let value = serde_json::to_value(value)?;
+        let value = self.bindings.bind_serialize("value", value)?;
+        let separator = if !self.update.is_empty() { ", " } else { "" };
         self.update = format!(
             "{}{}{} {} {}",
             self.update,
             separator,
             key,
             operator.to_str(),
-            serde_json::to_string(value)?
+            value
         );
         Ok(self)
     }
 
-    pub fn id(self, id: &str) -> Self {
-        let condition = format!("id = {}:{}", self.table, id);
-        self.condition(&condition)
+    /// Adds `condition`. If one of its fields is invalid it is left out and
+    /// the change fails with `Error::Validation` when run.
+    pub fn filter(mut self, condition: Condition) -> Self {
+        if let Err(Error::Validation(msg)) = condition.validate() {
+            self.invalid.get_or_insert(msg);
+            return self;
+        }
+        let condition = condition.render(&mut self.bindings);
+        self.and(&condition)
+    }
+
+    pub fn id<K: Into<Id>>(mut self, id: K) -> Self {
+        let id = self
+            .bindings
+            .bind("id", Thing::from((self.table, id.into())));
+        self.and(&format!("id == {}", id))
+    }
+
+    pub(crate) fn exclude_deleted(mut self) -> Self {
+        self.exclude_deleted = true;
+        self
+    }
+
+    /// Changes soft deleted records as well.
+    pub fn with_deleted(mut self) -> Self {
+        self.exclude_deleted = false;
+        self
+    }
+
+    pub(crate) fn record_history(mut self, changed_by: Option<String>) -> Self {
+        self.history = Some(self.bindings.bind("changed_by", changed_by));
+        self
+    }
+
+    pub fn bindings(&self) -> &Bindings {
+        &self.bindings
     }
 
     pub fn query_str(&self) -> String {
-        let mut query = format!("UPDATE {} SET {}", self.table, self.update);
+        let mut query = format!(
+            "UPDATE {} SET {}, updated_at = time::now(), version = (version ?? 0) + 1",
+            self.table, self.update
+        );
 
-        if self.condition.len() > 0 {
-            query = format!("{} WHERE {}", query, self.condition);
+        let condition = self.where_str();
+        if let Some(condition) = &condition {
+            query = format!("{} WHERE {}", query, condition);
+        }
+
+        if let Some(changed_by) = &self.history {
+            let source = match &condition {
+                Some(condition) => format!("{} WHERE {}", self.table, condition),
+                None => self.table.to_string(),
+            };
+            return format!(
+                "BEGIN TRANSACTION;\n{}{};\nCOMMIT TRANSACTION;",
+                history_str(self.table, &source, changed_by),
+                query
+            );
         }
 
         query + ";"
     }
 
+    fn where_str(&self) -> Option<String> {
+        match (self.condition.is_empty(), self.exclude_deleted) {
+            (true, false) => None,
+            (true, true) => Some("deleted_at IS NONE".to_string()),
+            (false, false) => Some(self.condition.clone()),
+            (false, true) => Some(format!("({}) AND deleted_at IS NONE", self.condition)),
+        }
+    }
+
     pub async fn query<T: Serialize + DeserializeOwned + Databasable>(
         &self,
     ) -> Result<Vec<T>, Error> {
-        Ok(self
+        if let Some(msg) = &self.invalid {
+            return Err(Error::validation(msg));
+        }
+        let mut response = self
             .client
             .query(self.query_str())
-            .await?
-            .take::<Vec<Record<T>>>(0)?
+            .bind(self.bindings.clone())
+            .await?;
+        if let Some((_, err)) = aborted(&mut response) {
+            return Err(err.into());
+        }
+        response
+            .take::<Vec<Record<T>>>(if self.history.is_some() { 1 } else { 0 })?
             .into_iter()
-            .map(|record: Record<T>| record.content())
-            .collect())
+            .map(Record::content)
+            .collect()
     }
 }
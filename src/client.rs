@@ -1,17 +1,44 @@
-use crate::{Change, Databasable, Error, Record, Select};
+use crate::error::aborted;
+use crate::hierarchy::{owner_condition, OWNER_TREE};
+use crate::history::{history_str, history_table};
+use crate::record::insert_str;
+use crate::retry::{retrying, Retry, Session};
+use crate::schema;
+use crate::traits::with_key;
+use crate::transaction::{authorization_str, Guard};
+use crate::{
+    field, Bindings, Change, ClientConfig, Credentials, Databasable, Error, Permission, Principal,
+    Record, RecordKey, RetryPolicy, Revision, Select, Transaction,
+};
 
 use futures::future::join_all;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
-use surrealdb::engine::remote::ws;
-use surrealdb::opt::auth::Root;
-use surrealdb::Surreal;
+use std::future::Future;
+use surrealdb::engine::any::{self, Any};
+use surrealdb::error::{Api, Db};
+use surrealdb::opt::auth::Jwt;
+use surrealdb::sql::{Id, Thing};
+use surrealdb::{RecordId, Response, Surreal};
+
+pub(crate) const IDEMPOTENCY: &str = "idempotency";
+/// How long an idempotency key keeps a write from being repeated.
+const IDEMPOTENCY_TTL: &str = "1d";
 
 #[derive(Debug, Clone)]
 pub struct Client<'a> {
-    client: Surreal<ws::Client>,
+    client: Surreal<Any>,
     table: Option<&'a str>,
+    principal: Option<Principal>,
     owners: Vec<String>,
+    read_owners: Vec<String>,
+    soft_delete: bool,
+    history: bool,
+    hierarchical: bool,
+    token: Option<Jwt>,
+    session: Option<Session>,
+    retry: Option<RetryPolicy>,
+    idempotency_key: Option<String>,
 }
 
 impl<'a> Client<'a> {
@@ -23,162 +50,778 @@ impl<'a> Client<'a> {
         namespace: &str,
         database: &str,
     ) -> Result<Self, Error> {
-        let client = Surreal::new::<ws::Ws>(format!("{}:{}", host, port)).await?;
-        client.signin(Root { username, password }).await?;
+        Self::connect(
+            host,
+            port,
+            namespace,
+            database,
+            Credentials::root(username, password),
+        )
+        .await
+    }
+
+    pub async fn namespace_user(
+        host: &str,
+        port: u16,
+        username: &str,
+        password: &str,
+        namespace: &str,
+        database: &str,
+    ) -> Result<Self, Error> {
+        Self::connect(
+            host,
+            port,
+            namespace,
+            database,
+            Credentials::namespace(username, password),
+        )
+        .await
+    }
+
+    pub async fn database_user(
+        host: &str,
+        port: u16,
+        username: &str,
+        password: &str,
+        namespace: &str,
+        database: &str,
+    ) -> Result<Self, Error> {
+        Self::connect(
+            host,
+            port,
+            namespace,
+            database,
+            Credentials::database(username, password),
+        )
+        .await
+    }
+
+    /// Connects as a record user of `access` instead of Root, so the
+    /// permissions from `schema::define_table` are enforced by the database.
+    /// The client acts as `Principal::User` with the user's record id.
+    pub async fn record_user<P: Serialize>(
+        host: &str,
+        port: u16,
+        namespace: &str,
+        database: &str,
+        access: &str,
+        params: P,
+    ) -> Result<Self, Error> {
+        Self::connect(
+            host,
+            port,
+            namespace,
+            database,
+            Credentials::record_signin(access, params)?,
+        )
+        .await
+    }
+
+    /// Like `record_user`, but signs a new record user up first.
+    pub async fn record_signup<P: Serialize>(
+        host: &str,
+        port: u16,
+        namespace: &str,
+        database: &str,
+        access: &str,
+        params: P,
+    ) -> Result<Self, Error> {
+        Self::connect(
+            host,
+            port,
+            namespace,
+            database,
+            Credentials::record_signup(access, params)?,
+        )
+        .await
+    }
+
+    /// Connects with a token issued before, acting as its record user if it
+    /// belongs to one. Tokens of system users carry no principal, so such a
+    /// client reaches no records until `principal` or `owner` is set.
+    pub async fn authenticate(
+        host: &str,
+        port: u16,
+        namespace: &str,
+        database: &str,
+        token: impl Into<Jwt>,
+    ) -> Result<Self, Error> {
+        Self::connect(host, port, namespace, database, Credentials::token(token)).await
+    }
+
+    pub async fn connect(
+        host: &str,
+        port: u16,
+        namespace: &str,
+        database: &str,
+        credentials: Credentials,
+    ) -> Result<Self, Error> {
+        Self::from_config(
+            &ClientConfig::new(host, port, namespace, database).with_credentials(credentials),
+        )
+        .await
+    }
+
+    /// Connects as described by `config`, giving up after its connect
+    /// timeout with `Error::Connection`. Connecting is retried with the
+    /// config's retry policy, which the client then keeps, see `retry`.
+    pub async fn from_config(config: &ClientConfig) -> Result<Self, Error> {
+        let retry = config.retry().map(|policy| Retry {
+            policy: policy.clone(),
+            session: None,
+        });
+        let client = retrying(retry.as_ref(), || Self::connect_once(config)).await?;
+        Ok(Self {
+            retry: config.retry().cloned(),
+            ..client
+        })
+    }
+
+    async fn connect_once(config: &ClientConfig) -> Result<Self, Error> {
+        let endpoint = config.endpoint();
+        let client = match config.connect_timeout() {
+            Some(timeout) => tokio::time::timeout(timeout, any::connect(&endpoint))
+                .await
+                .map_err(|_| {
+                    surrealdb::Error::Api(Api::Ws(format!(
+                        "connecting to {} timed out after {:?}",
+                        endpoint, timeout
+                    )))
+                })??,
+            None => any::connect(&endpoint).await?,
+        };
+        match config.credentials() {
+            Some(credentials) => {
+                Self::signin(
+                    client,
+                    config.namespace(),
+                    config.database(),
+                    credentials.clone(),
+                )
+                .await
+            }
+            None => {
+                client
+                    .use_ns(config.namespace())
+                    .use_db(config.database())
+                    .await?;
+                Ok(Self {
+                    session: Some(Session::new(
+                        client.clone(),
+                        config.namespace(),
+                        config.database(),
+                        None,
+                    )),
+                    ..Self::from_surreal(client)
+                })
+            }
+        }
+    }
+
+    /// Authenticates an existing connection with `credentials` and selects
+    /// `namespace` and `database`.
+    pub async fn signin(
+        client: Surreal<Any>,
+        namespace: &str,
+        database: &str,
+        credentials: Credentials,
+    ) -> Result<Self, Error> {
+        let token = credentials
+            .authenticate(&client, namespace, database)
+            .await?;
+        client.use_ns(namespace).use_db(database).await?;
+        let client = Self {
+            token: Some(token),
+            session: Some(Session::new(
+                client.clone(),
+                namespace,
+                database,
+                Some(credentials.clone()),
+            )),
+            ..Self::from_surreal(client)
+        };
+        if credentials.is_record() {
+            client.auth_owner().await
+        } else {
+            Ok(client)
+        }
+    }
+
+    #[cfg(any(feature = "kv-mem", test))]
+    pub async fn memory(namespace: &str, database: &str) -> Result<Self, Error> {
+        let client = any::connect("mem://").await?;
         client.use_ns(namespace).use_db(database).await?;
         Ok(Self {
+            session: Some(Session::new(client.clone(), namespace, database, None)),
+            ..Self::from_surreal(client)
+        })
+    }
+
+    pub fn from_surreal(client: Surreal<Any>) -> Self {
+        Self {
             client,
             table: None,
+            principal: None,
             owners: vec![],
+            read_owners: vec![],
+            soft_delete: false,
+            history: false,
+            hierarchical: false,
+            token: None,
+            session: None,
+            retry: None,
+            idempotency_key: None,
+        }
+    }
+
+    /// Acts as the record user the connection is signed in as, if any, and
+    /// otherwise stays without principal.
+    async fn auth_owner(self) -> Result<Self, Error> {
+        let owner: Option<String> = self
+            .client
+            .query("RETURN IF $auth THEN <string> $auth END;")
+            .await?
+            .take(0)?;
+        Ok(match owner {
+            Some(owner) => self.principal(Principal::User(owner)),
+            None => self,
         })
     }
 
+    /// The token of the session, if the client signed in.
+    pub fn token(&self) -> Option<&Jwt> {
+        self.token.as_ref()
+    }
+
     pub fn table(&self, table: &'a str) -> Self {
         Self {
-            client: self.client.clone(),
             table: Some(table),
-            owners: self.owners.clone(),
+            ..self.clone()
         }
     }
 
-    pub fn owner(&self, owner: &'a str) -> Self {
+    pub fn table_of<T: Databasable>(&self) -> Self {
         Self {
-            client: self.client.clone(),
-            table: self.table.clone(),
-            owners: vec![owner.to_string()],
+            table: T::table(),
+            ..self.clone()
         }
     }
 
+    pub fn owner(&self, owner: &'a str) -> Self {
+        self.principal(Principal::User(owner.to_string()))
+    }
+
+    /// Acts as a user owning all of `owners`, new records belong to the first.
     pub fn owners(&self, owners: Vec<String>) -> Self {
         Self {
-            client: self.client.clone(),
-            table: self.table.clone(),
+            principal: owners.first().cloned().map(Principal::User),
             owners,
+            ..self.clone()
+        }
+    }
+
+    /// Additionally allows reading records of `owners` without being able to
+    /// change them.
+    pub fn read_owners(&self, owners: Vec<String>) -> Self {
+        Self {
+            read_owners: owners,
+            ..self.clone()
+        }
+    }
+
+    /// Limits changes to records of `owners`, new records belong to the
+    /// first. Records of the previous owners stay readable.
+    pub fn write_owners(&self, owners: Vec<String>) -> Self {
+        let mut read_owners = self.read_owners.clone();
+        for owner in &self.owners {
+            if !owners.contains(owner) && !read_owners.contains(owner) {
+                read_owners.push(owner.clone());
+            }
+        }
+        Self {
+            owners,
+            read_owners,
+            ..self.clone()
+        }
+    }
+
+    /// Acts as `principal`. `Admin` and `Service` are not limited to their
+    /// own records and keep the owners set before for records they create.
+    /// The `read_owners` are kept either way.
+    pub fn principal(&self, principal: Principal) -> Self {
+        let owners = match &principal {
+            Principal::User(id) => vec![id.clone()],
+            _ => self.owners.clone(),
+        };
+        Self {
+            principal: Some(principal),
+            owners,
+            ..self.clone()
+        }
+    }
+
+    /// Makes `delete_one` and `delete` only mark records with `deleted_at`,
+    /// which `Select` then leaves out unless `with_deleted` is called.
+    pub fn soft_delete(&self) -> Self {
+        Self {
+            soft_delete: true,
+            ..self.clone()
+        }
+    }
+
+    /// Keeps the previous state of every record changed or deleted through
+    /// this client in `<table>_history`.
+    pub fn history(&self) -> Self {
+        Self {
+            history: true,
+            ..self.clone()
+        }
+    }
+
+    /// Retries reads that fail with `Error::Connection` as `policy` says,
+    /// signing in and selecting the namespace and database again before each
+    /// retry. Writes are only retried with an `idempotency_key`.
+    pub fn retry(&self, policy: RetryPolicy) -> Self {
+        Self {
+            retry: Some(policy),
+            ..self.clone()
+        }
+    }
+
+    /// Makes single record writes apply at most once per record for `key`,
+    /// so they can be retried like reads. Repeating a write already made with
+    /// the key returns the record as it is stored now, using the key for
+    /// another write on the record fails with `Error::Validation`. Keys
+    /// expire after a day. Records created with a key need an id.
+    pub fn idempotency_key(&self, key: &str) -> Self {
+        Self {
+            idempotency_key: Some(key.to_string()),
+            ..self.clone()
+        }
+    }
+
+    /// Defines the client's table with the permissions of
+    /// `schema::define_table`, keeping an existing definition.
+    pub async fn define_table(&self) -> Result<(), Error> {
+        self.client
+            .query(schema::define_table(self.get_table()?)?)
+            .query(schema::define_idempotency_table())
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    /// Like `define_table`, but replaces an existing definition.
+    pub async fn redefine_table(&self) -> Result<(), Error> {
+        self.client
+            .query(schema::redefine_table(self.get_table()?)?)
+            .query(schema::define_idempotency_table())
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    /// Extends every owner the client acts for to its descendants in the
+    /// owner hierarchy, see `set_parent`.
+    pub fn hierarchy(&self) -> Self {
+        Self {
+            hierarchical: true,
+            ..self.clone()
         }
     }
 
     pub fn select(self) -> Result<Select<'a>, Error> {
-        Ok(Select::new(
-            self.client.clone(),
-            self.get_table()?,
-            self.owners,
-        ))
+        let select = match self.read_scope() {
+            None => Select::new(self.client.clone(), self.get_table()?, vec![]),
+            Some(owners) if owners.is_empty() => {
+                Select::new(self.client.clone(), self.get_table()?, vec![])
+                    .filter(field("owner").inside(Vec::<String>::new()))
+            }
+            Some(owners) => Select::scoped(
+                self.client.clone(),
+                self.get_table()?,
+                owners,
+                self.hierarchical,
+            ),
+        }
+        .with_retry(self.retrier());
+        Ok(if self.soft_delete {
+            select.exclude_deleted()
+        } else {
+            select
+        })
     }
 
     pub fn change(self) -> Result<Change<'a>, Error> {
-        Ok(Change::new(
-            self.client.clone(),
-            self.get_table()?,
-            self.owners,
-        ))
+        let changed_by = self.actor();
+        let change = match self.write_scope() {
+            None => Change::new(self.client.clone(), self.get_table()?, vec![]),
+            Some([]) => Change::new(self.client.clone(), self.get_table()?, vec![])
+                .filter(field("owner").inside(Vec::<String>::new())),
+            Some(owners) => Change::scoped(
+                self.client.clone(),
+                self.get_table()?,
+                owners.to_vec(),
+                self.hierarchical,
+            ),
+        };
+        let change = if self.soft_delete {
+            change.exclude_deleted()
+        } else {
+            change
+        };
+        Ok(if self.history {
+            change.record_history(changed_by)
+        } else {
+            change
+        })
+    }
+
+    /// Runs `f` with a `Transaction` that queues create, update and delete
+    /// statements. They are only sent to the database if `f` succeeds and are
+    /// then committed or rolled back as a whole.
+    pub async fn transaction<F, Fut, R>(&self, f: F) -> Result<R, Error>
+    where
+        F: FnOnce(Transaction<'a>) -> Fut,
+        Fut: Future<Output = Result<R, Error>>,
+    {
+        let transaction = Transaction::new(self.clone());
+        let result = f(transaction.clone()).await?;
+        transaction.commit().await?;
+        Ok(result)
     }
 
     fn get_table(&self) -> Result<&'a str, Error> {
-        self.table.ok_or(Error::new("table is none"))
+        self.table.ok_or(Error::MissingTable)
+    }
+
+    pub(crate) fn get_table_for<T: Databasable>(&self) -> Result<&'a str, Error> {
+        self.table.or(T::table()).ok_or(Error::MissingTable)
     }
 
-    fn first_owner(&self) -> Option<String> {
+    pub(crate) fn first_owner(&self) -> Option<String> {
         self.owners.first().map(|owner| owner.to_string())
     }
 
-    async fn authorized(&self, id: &str) -> Result<(), Error> {
-        if self.owners.len() == 0 {
-            return Ok(());
+    pub(crate) fn surreal(&self) -> &Surreal<Any> {
+        &self.client
+    }
+
+    pub(crate) fn soft_deletes(&self) -> bool {
+        self.soft_delete
+    }
+
+    pub(crate) fn hierarchical(&self) -> bool {
+        self.hierarchical
+    }
+
+    pub(crate) fn retrier(&self) -> Option<Retry> {
+        self.retry.clone().map(|policy| Retry {
+            policy,
+            session: self.session.clone(),
+        })
+    }
+
+    /// Only `Admin` and `Service` principals reach every record, a client
+    /// without principal or owners reaches none.
+    fn unrestricted(&self) -> bool {
+        self.principal
+            .as_ref()
+            .is_some_and(Principal::is_privileged)
+    }
+
+    /// Owners whose records the client may read, `None` if it may read every
+    /// record.
+    pub(crate) fn read_scope(&self) -> Option<Vec<String>> {
+        if self.unrestricted() {
+            return None;
         }
-        if self.owners.contains(&"admin".to_string()) {
-            return Ok(());
+        let mut owners = self.owners.clone();
+        for owner in &self.read_owners {
+            if !owners.contains(owner) {
+                owners.push(owner.clone());
+            }
         }
+        Some(owners)
+    }
 
-        let table = self.get_table()?;
-        let mut response = self
-            .client
-            .query(format!(
-                "count(SELECT id FROM {} WHERE id == {}:{}) == 1;",
-                &table, &table, id,
-            ))
-            .query(if self.owners.len() > 1 {
-                format!(
-                    "count(SELECT id FROM {} WHERE id =={}:{} AND owner in [{}]) == 1;",
-                    &table,
-                    &table,
-                    id,
-                    self.owners
-                        .iter()
-                        .map(|owner| format!("\"{}\"", owner))
-                        .collect::<Vec<String>>()
-                        .join(","),
+    /// Fails with `Error::Unauthorized` if the client may change no record
+    /// at all, e.g. before writing `id` of `table`.
+    pub(crate) fn writable(&self, table: &str, id: Option<&Id>) -> Result<(), Error> {
+        match self.write_scope() {
+            Some([]) => Err(Error::Unauthorized {
+                table: table.to_string(),
+                id: id.map_or_else(|| "*".to_string(), Id::to_raw),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Owners whose records the client may change, `None` if it may change
+    /// every record.
+    pub(crate) fn write_scope(&self) -> Option<&[String]> {
+        if self.unrestricted() {
+            None
+        } else {
+            Some(&self.owners)
+        }
+    }
+
+    /// Name recorded as `changed_by` in the history.
+    fn actor(&self) -> Option<String> {
+        match &self.principal {
+            Some(Principal::Service(name)) => Some(name.clone()),
+            _ => self.first_owner(),
+        }
+    }
+
+    /// Statement archiving the records of `source` if history is enabled.
+    pub(crate) fn history_str(&self, bindings: &mut Bindings, table: &str, source: &str) -> String {
+        if !self.history {
+            return String::new();
+        }
+        let changed_by = bindings.bind("changed_by", self.actor());
+        history_str(table, source, &changed_by)
+    }
+
+    /// The statement throwing unless the client owns the record `target` or
+    /// is listed in one of its access lists `shared`, with what it stands
+    /// for. `None` if the client may change every record.
+    fn authorization(
+        &self,
+        bindings: &mut Bindings,
+        target: &str,
+        table: &str,
+        id: &Id,
+        shared: &[&str],
+    ) -> Result<Option<(String, Guard)>, Error> {
+        self.writable(table, Some(id))?;
+        let Some(owners) = self.write_scope() else {
+            return Ok(None);
+        };
+        let allowed = owner_condition(bindings, owners.to_vec(), shared, self.hierarchical);
+        Ok(Some((
+            authorization_str(target, &allowed),
+            Guard::Unauthorized {
+                table: table.to_string(),
+                id: id.to_raw(),
+            },
+        )))
+    }
+
+    /// Runs the write `statements` on `target` in one transaction, after
+    /// `guard`, at most once per idempotency key and retried if the client
+    /// has one. `take` reads the response from the statement index it is
+    /// given. `None` if the write was already made.
+    async fn write<R, F>(
+        &self,
+        bindings: &Bindings,
+        target: &str,
+        guard: Option<(String, Guard)>,
+        statements: &str,
+        take: F,
+    ) -> Result<Option<R>, Error>
+    where
+        F: Fn(Response, usize) -> Result<R, Error>,
+    {
+        let (guard, statements) = match guard {
+            Some((statement, guard)) => (Some(guard), format!("{} {}", statement, statements)),
+            None => (None, statements.to_string()),
+        };
+        let offset = usize::from(guard.is_some());
+        let Some(key) = &self.idempotency_key else {
+            let statements = format!("BEGIN TRANSACTION; {} COMMIT TRANSACTION;", statements);
+            let response = self
+                .transact(
+                    &statements,
+                    bindings,
+                    guard.as_ref().map(|guard| (0, guard)),
                 )
-            } else {
-                format!(
-                    "count(SELECT id FROM {} WHERE id =={}:{} AND owner == \"{}\") == 1;",
-                    &table, &table, id, self.owners[0],
+                .await?;
+            return take(response, offset).map(Some);
+        };
+
+        // The write with its values identifies the request made with the key.
+        let request = bindings.inline(&statements);
+        let mut bindings = bindings.clone();
+        let record = format!(
+            "type::thing('{}', [{}, {}])",
+            IDEMPOTENCY,
+            bindings.bind("idempotency_key", key.clone()),
+            target
+        );
+        let request = bindings.bind("idempotency_request", request);
+        let owner = bindings.bind("idempotency_owner", self.first_owner());
+        let statements = format!(
+            "BEGIN TRANSACTION; \
+             DELETE {table} WHERE created_at < time::now() - {ttl}; \
+             CREATE {record} SET owner = {owner}, request = crypto::sha256({request}), created_at = time::now(); \
+             {statements} COMMIT TRANSACTION;",
+            table = IDEMPOTENCY,
+            ttl = IDEMPOTENCY_TTL,
+            record = record,
+            owner = owner,
+            request = request,
+            statements = statements
+        );
+        retrying(self.retrier().as_ref(), || async {
+            match self
+                .transact(
+                    &statements,
+                    &bindings,
+                    guard.as_ref().map(|guard| (2, guard)),
                 )
-            })
-            .await?;
-        let exists: Vec<bool> = response.take(0)?;
-        if !exists[0] {
-            Ok(())
-        } else {
-            let authorized: Vec<bool> = response.take(1)?;
-            if authorized[0] {
-                Ok(())
-            } else {
-                Err(Error::new("not authorized"))
+                .await
+            {
+                Ok(response) => take(response, 2 + offset).map(Some),
+                Err(Error::Conflict { table, .. }) if table == IDEMPOTENCY => {
+                    let repeated: Option<bool> = self
+                        .client
+                        .query(format!(
+                            "RETURN (SELECT VALUE request FROM ONLY {}) == crypto::sha256({});",
+                            record, request
+                        ))
+                        .bind(bindings.clone())
+                        .await?
+                        .take(0)?;
+                    if repeated.unwrap_or_default() {
+                        Ok(None)
+                    } else {
+                        Err(Error::validation(&format!(
+                            "idempotency key {:?} was used for another write",
+                            key
+                        )))
+                    }
+                }
+                Err(err) => Err(err),
+            }
+        })
+        .await
+    }
+
+    /// Runs the transaction `statements`, failing with the error of the
+    /// statement that aborted it, or with the error of `guard` if the guard
+    /// statement at its index threw.
+    async fn transact(
+        &self,
+        statements: &str,
+        bindings: &Bindings,
+        guard: Option<(usize, &Guard)>,
+    ) -> Result<Response, Error> {
+        let mut response = self.client.query(statements).bind(bindings.clone()).await?;
+        match (aborted(&mut response), guard) {
+            (Some((index, surrealdb::Error::Db(Db::Thrown(_)))), Some((at, guard)))
+                if index == at =>
+            {
+                Err(guard.clone().into())
             }
+            (Some((_, err)), _) => Err(err.into()),
+            (None, _) => Ok(response),
         }
     }
 
+    /// The record `id` as it is stored now.
+    async fn stored<T: Databasable + Serialize + DeserializeOwned>(
+        &self,
+        table: &str,
+        id: &Id,
+    ) -> Result<Vec<Record<T>>, Error> {
+        retrying(self.retrier().as_ref(), || async {
+            Ok(self
+                .client
+                .query("SELECT * FROM $id;")
+                .bind(("id", Thing::from((table, id.clone()))))
+                .await?
+                .take(0)?)
+        })
+        .await
+    }
+
+    /// Deletes every record of `table`, which only `Admin` and `Service`
+    /// principals may do.
     pub async fn drop_table<T: Databasable + Serialize + DeserializeOwned>(
         &self,
         table: &str,
     ) -> Result<Vec<T>, Error> {
-        Ok(self
-            .client
+        if self.write_scope().is_some() {
+            return Err(Error::Unauthorized {
+                table: table.to_string(),
+                id: "*".to_string(),
+            });
+        }
+        self.client
             .delete(table)
             .await?
             .into_iter()
-            .map(|record: Record<T>| record.content())
-            .collect())
+            .map(Record::content)
+            .collect()
     }
 
     pub async fn create_one<T: Databasable + Serialize + DeserializeOwned + 'static>(
         &self,
         content: T,
     ) -> Result<Vec<T>, Error> {
-        let table = self.table.clone().ok_or(Error::new("no table given"))?;
-        if let Some(id) = content.get_id() {
-            self.client
-                .create((table, id))
-                .content(Record::new(content, table.to_string(), self.first_owner()))
-                .await?
-                .map(|record: Record<T>| vec![record.content()])
-                .ok_or(Error::new("record is none"))
-        } else {
-            Ok(self
-                .client
-                .create(table)
-                .content(Record::new(content, table.to_string(), self.first_owner()))
+        let table = self.get_table_for::<T>()?;
+        let content = with_key(content);
+        let id = content.get_id().map(RecordKey::into_id);
+        self.writable(table, id.as_ref())?;
+        let mut bindings = Bindings::new();
+        let target = match &id {
+            Some(id) => bindings.bind("id", Thing::from((table, id.clone()))),
+            None => table.to_string(),
+        };
+        let statement = Record::new(content, table.to_string(), self.first_owner())
+            .create_str(&mut bindings, &target)?;
+        let records = match id {
+            Some(id) => match self
+                .write(
+                    &bindings,
+                    &target,
+                    None,
+                    &statement,
+                    |mut response, offset| Ok(response.take(offset)?),
+                )
                 .await?
-                .into_iter()
-                .map(|record: Record<T>| record.content())
-                .collect())
-        }
+            {
+                Some(records) => records,
+                None => self.stored(table, &id).await?,
+            },
+            None if self.idempotency_key.is_some() => {
+                return Err(Error::validation(
+                    "records created with an idempotency key need an id",
+                ))
+            }
+            None => self.client.query(statement).bind(bindings).await?.take(0)?,
+        };
+        records.into_iter().map(Record::content).collect()
     }
 
     pub async fn create<T: Databasable + Serialize + DeserializeOwned + 'static>(
         &self,
         content: Vec<T>,
     ) -> Result<Vec<T>, Error> {
-        join_all(content.into_iter().map(|content| self.create_one(content)))
-            .await
+        if content.is_empty() {
+            return Ok(Vec::new());
+        }
+        if self.idempotency_key.is_some() {
+            return join_writes(content.into_iter().map(|content| self.create_one(content))).await;
+        }
+        let table = self.get_table_for::<T>()?;
+        self.writable(table, None)?;
+        let records = content
             .into_iter()
-            .try_fold(Vec::new(), |acc, result| {
-                result.and_then(|inner_vec| {
-                    let mut acc = acc;
-                    acc.extend(inner_vec);
-                    Ok(acc)
-                })
-            })
+            .map(|content| Record::new(with_key(content), table.to_string(), self.first_owner()))
+            .collect::<Vec<Record<T>>>();
+        let mut bindings = Bindings::new();
+        let statement = insert_str(&mut bindings, table, records)?;
+        self.client
+            .query(statement)
+            .bind(bindings)
+            .await?
+            .take::<Vec<Record<T>>>(0)?
+            .into_iter()
+            .map(Record::content)
+            .collect()
     }
 
     pub async fn update_one<
@@ -187,26 +830,113 @@ impl<'a> Client<'a> {
         &self,
         content: T,
     ) -> Result<Vec<T>, Error> {
-        let table = self.table.clone().ok_or(Error::new("no table given"))?;
-        let id = content.get_id().ok_or(Error::new("no id given"))?;
-        self.authorized(&id).await?;
-
-        let update_result: Option<Record<T>> = self
-            .client
-            .update((table, id))
-            .content(Record::new(
-                content.clone(),
-                table.to_string(),
-                self.first_owner(),
-            ))
-            .await?;
+        let table = self.get_table_for::<T>()?;
+        let id = content
+            .get_id()
+            .ok_or(Error::MissingId {
+                table: table.to_string(),
+            })?
+            .into_id();
+        let mut bindings = Bindings::new();
+        let target = bindings.bind("id", Thing::from((table, id.clone())));
+        let guard = self.authorization(&mut bindings, &target, table, &id, &["editors"])?;
+        let history = self.history_str(&mut bindings, table, &target);
+        let statement = Record::new(content, table.to_string(), self.first_owner())
+            .upsert_str(&mut bindings, &target)?;
+        let index = usize::from(self.history);
+        let records = match self
+            .write(
+                &bindings,
+                &target,
+                guard,
+                &(history + &statement),
+                |mut response, offset| Ok(response.take::<Vec<Record<T>>>(offset + index)?),
+            )
+            .await?
+        {
+            Some(records) => records,
+            None => self.stored(table, &id).await?,
+        };
+        records.into_iter().map(Record::content).collect()
+    }
 
-        let result = match update_result {
-            Some(record) => vec![record.content()],
-            None => self.create_one(content).await?,
+    /// Updates `content` only if the stored record is still at `version`,
+    /// otherwise fails with `Error::Conflict`.
+    pub async fn update_one_if_version<T: Databasable + Serialize + DeserializeOwned + 'static>(
+        &self,
+        content: T,
+        version: u64,
+    ) -> Result<Vec<T>, Error> {
+        let table = self.get_table_for::<T>()?;
+        let id = content
+            .get_id()
+            .ok_or(Error::MissingId {
+                table: table.to_string(),
+            })?
+            .into_id();
+        let mut bindings = Bindings::new();
+        let target = bindings.bind("id", Thing::from((table, id.clone())));
+        let guard = self.authorization(&mut bindings, &target, table, &id, &["editors"])?;
+        let version = bindings.bind("version", version as i64);
+        let history = self.history_str(
+            &mut bindings,
+            table,
+            &format!("{} WHERE version == {}", target, version),
+        );
+        let statement = Record::new(content, table.to_string(), self.first_owner())
+            .update_if_version_str(&mut bindings, &target, &version)?;
+        let statements = format!(
+            "{}{} count(SELECT id FROM {}) == 1;",
+            history, statement, target
+        );
+        let index = usize::from(self.history);
+        let (records, exists) = match self
+            .write(
+                &bindings,
+                &target,
+                guard,
+                &statements,
+                |mut response, offset| {
+                    let records: Vec<Record<T>> = response.take(offset + index)?;
+                    let exists: Option<bool> = response.take(offset + index + 1)?;
+                    Ok((records, exists))
+                },
+            )
+            .await?
+        {
+            Some(result) => result,
+            None => (self.stored(table, &id).await?, None),
         };
+        if !records.is_empty() {
+            return records.into_iter().map(Record::content).collect();
+        }
+        if exists.unwrap_or_default() {
+            Err(Error::Conflict {
+                table: table.to_string(),
+                id: id.to_raw(),
+                source: None,
+            })
+        } else {
+            Err(Error::NotFound {
+                table: table.to_string(),
+                id: Some(id.to_raw()),
+            })
+        }
+    }
 
-        Ok(result)
+    /// Moves the records `ids` to `new_owner` in one transaction, which
+    /// fails unless the client owns each of them. Readers and editors the
+    /// records were shared with lose their access.
+    pub async fn transfer_owner<T: Databasable, K: Into<Id>>(
+        &self,
+        ids: Vec<K>,
+        new_owner: &str,
+    ) -> Result<(), Error> {
+        let ids = ids.into_iter().map(Into::into).collect::<Vec<Id>>();
+        self.transaction(
+            |transaction| async move { transaction.transfer_owner::<T, _>(ids, new_owner) },
+        )
+        .await
     }
 
     pub async fn update<
@@ -215,45 +945,333 @@ impl<'a> Client<'a> {
         &self,
         content: Vec<T>,
     ) -> Result<Vec<T>, Error> {
-        join_all(content.into_iter().map(|content| self.update_one(content)))
-            .await
-            .into_iter()
-            .try_fold(Vec::new(), |acc, result| {
-                result.and_then(|inner_vec| {
-                    let mut acc = acc;
-                    acc.extend(inner_vec);
-                    Ok(acc)
-                })
-            })
+        join_writes(content.into_iter().map(|content| self.update_one(content))).await
     }
 
     pub async fn delete_one<T: Databasable + Serialize + DeserializeOwned + 'static>(
         &self,
         content: T,
     ) -> Result<Vec<T>, Error> {
-        let table = self.table.clone().ok_or(Error::new("no table given"))?;
-        let id = content.get_id().ok_or(Error::new("no id given"))?;
-        self.authorized(&id).await?;
-        self.client
-            .delete((table, id))
-            .await?
-            .map(|record: Record<T>| vec![record.content()])
-            .ok_or(Error::new("record is none"))
+        if self.soft_delete {
+            self.mark_deleted(content, "time::now()").await
+        } else {
+            self.purge_one(content).await
+        }
     }
 
     pub async fn delete<T: Databasable + Serialize + DeserializeOwned + 'static>(
         &self,
         content: Vec<T>,
     ) -> Result<Vec<T>, Error> {
-        join_all(content.into_iter().map(|content| self.delete_one(content)))
-            .await
-            .into_iter()
-            .try_fold(Vec::new(), |acc, result| {
-                result.and_then(|inner_vec| {
-                    let mut acc = acc;
-                    acc.extend(inner_vec);
-                    Ok(acc)
-                })
+        join_writes(content.into_iter().map(|content| self.delete_one(content))).await
+    }
+
+    pub async fn restore_one<T: Databasable + Serialize + DeserializeOwned + 'static>(
+        &self,
+        content: T,
+    ) -> Result<Vec<T>, Error> {
+        self.mark_deleted(content, "NONE").await
+    }
+
+    pub async fn restore<T: Databasable + Serialize + DeserializeOwned + 'static>(
+        &self,
+        content: Vec<T>,
+    ) -> Result<Vec<T>, Error> {
+        join_writes(content.into_iter().map(|content| self.restore_one(content))).await
+    }
+
+    /// Removes the record for good, also in soft delete mode.
+    pub async fn purge_one<T: Databasable + Serialize + DeserializeOwned + 'static>(
+        &self,
+        content: T,
+    ) -> Result<Vec<T>, Error> {
+        let table = self.get_table_for::<T>()?;
+        let id = content
+            .get_id()
+            .ok_or(Error::MissingId {
+                table: table.to_string(),
+            })?
+            .into_id();
+        let mut bindings = Bindings::new();
+        let target = bindings.bind("id", Thing::from((table, id.clone())));
+        let guard = self.authorization(&mut bindings, &target, table, &id, &[])?;
+        let history = self.history_str(&mut bindings, table, &target);
+        let index = usize::from(self.history);
+        let records: Vec<Record<T>> = match self
+            .write(
+                &bindings,
+                &target,
+                guard,
+                &format!("{}DELETE {} RETURN BEFORE;", history, target),
+                |mut response, offset| Ok(response.take(offset + index)?),
+            )
+            .await?
+        {
+            Some(records) => records,
+            None => return Ok(vec![]),
+        };
+        if records.is_empty() {
+            return Err(Error::NotFound {
+                table: table.to_string(),
+                id: Some(id.to_raw()),
+            });
+        }
+        records.into_iter().map(Record::content).collect()
+    }
+
+    pub async fn purge<T: Databasable + Serialize + DeserializeOwned + 'static>(
+        &self,
+        content: Vec<T>,
+    ) -> Result<Vec<T>, Error> {
+        join_writes(content.into_iter().map(|content| self.purge_one(content))).await
+    }
+
+    async fn mark_deleted<T: Databasable + Serialize + DeserializeOwned + 'static>(
+        &self,
+        content: T,
+        deleted_at: &str,
+    ) -> Result<Vec<T>, Error> {
+        let table = self.get_table_for::<T>()?;
+        let id = content
+            .get_id()
+            .ok_or(Error::MissingId {
+                table: table.to_string(),
+            })?
+            .into_id();
+        let mut bindings = Bindings::new();
+        let target = bindings.bind("id", Thing::from((table, id.clone())));
+        let guard = self.authorization(&mut bindings, &target, table, &id, &[])?;
+        let history = self.history_str(&mut bindings, table, &target);
+        let index = usize::from(self.history);
+        let records: Vec<Record<T>> = match self
+            .write(
+                &bindings,
+                &target,
+                guard,
+                &format!(
+                    "{}UPDATE {} SET deleted_at = {}, updated_at = time::now(), version = (version ?? 0) + 1;",
+                    history, target, deleted_at
+                ),
+                |mut response, offset| Ok(response.take(offset + index)?),
+            )
+            .await?
+        {
+            Some(records) => records,
+            None => self.stored(table, &id).await?,
+        };
+        if records.is_empty() {
+            return Err(Error::NotFound {
+                table: table.to_string(),
+                id: Some(id.to_raw()),
+            });
+        }
+        records.into_iter().map(Record::content).collect()
+    }
+
+    /// Lists the archived revisions of the record `id`, oldest first.
+    pub async fn revisions<T: Databasable + Serialize + DeserializeOwned, K: Into<Id>>(
+        &self,
+        id: K,
+    ) -> Result<Vec<Revision<T>>, Error> {
+        let table = self.get_table_for::<T>()?;
+        let mut bindings = Bindings::new();
+        let record = bindings.bind("record", Thing::from((table, id.into())));
+        let mut query = format!(
+            "SELECT * FROM {} WHERE record == {}",
+            history_table(table),
+            record
+        );
+        if let Some(owners) = self.read_scope() {
+            let allowed = owner_condition(&mut bindings, owners, &[], self.hierarchical);
+            query = format!("{} AND {}", query, allowed);
+        }
+        let query = query + " ORDER BY changed_at ASC;";
+        retrying(self.retrier().as_ref(), || async {
+            Ok(self
+                .client
+                .query(query.as_str())
+                .bind(bindings.clone())
+                .await?
+                .take(0)?)
+        })
+        .await
+    }
+
+    /// Writes the content of `revision` back to its record.
+    pub async fn revert<
+        T: Databasable + Serialize + DeserializeOwned + Clone + 'static + std::fmt::Debug,
+    >(
+        &self,
+        revision: Revision<T>,
+    ) -> Result<Vec<T>, Error> {
+        self.update_one(revision.content()?).await
+    }
+
+    /// Gives `user` access to the record `id`. Only owners of the record can
+    /// share it.
+    pub async fn share<T: Databasable, K: Into<Id>>(
+        &self,
+        id: K,
+        user: &str,
+        permission: Permission,
+    ) -> Result<(), Error> {
+        let field = match permission {
+            Permission::Read => "readers",
+            Permission::Edit => "editors",
+        };
+        self.change_access::<T>(
+            id.into(),
+            user,
+            &format!("{0} = array::union({0} ?? [], [$user])", field),
+        )
+        .await
+    }
+
+    /// Takes back any access `user` was given to the record `id`.
+    pub async fn unshare<T: Databasable, K: Into<Id>>(
+        &self,
+        id: K,
+        user: &str,
+    ) -> Result<(), Error> {
+        self.change_access::<T>(
+            id.into(),
+            user,
+            "readers = array::complement(readers ?? [], [$user]), editors = array::complement(editors ?? [], [$user])",
+        )
+        .await
+    }
+
+    async fn change_access<T: Databasable>(
+        &self,
+        id: Id,
+        user: &str,
+        set: &str,
+    ) -> Result<(), Error> {
+        let table = self.get_table_for::<T>()?;
+        let mut bindings = Bindings::new();
+        let record = bindings.bind("id", Thing::from((table, id.clone())));
+        // `set` refers to the user as `$user`
+        bindings.bind("user", user.to_string());
+        let mut query = format!("UPDATE {} SET {}", record, set);
+        if let Some(owners) = self.write_scope() {
+            let allowed = owner_condition(&mut bindings, owners.to_vec(), &[], self.hierarchical);
+            query = format!("{} WHERE {}", query, allowed);
+        }
+        let mut response = self
+            .client
+            .query(query + ";")
+            .query(format!("count(SELECT id FROM {}) == 1;", record))
+            .bind(bindings)
+            .await?;
+        let updated: Vec<RecordId> = response.take((0, "id"))?;
+        if !updated.is_empty() {
+            return Ok(());
+        }
+        let exists: Option<bool> = response.take(1)?;
+        if exists.unwrap_or_default() {
+            Err(Error::Unauthorized {
+                table: table.to_string(),
+                id: id.to_raw(),
+            })
+        } else {
+            Err(Error::NotFound {
+                table: table.to_string(),
+                id: Some(id.to_raw()),
             })
+        }
+    }
+
+    /// Places `owner` below `parent` in the owner hierarchy, or makes it a
+    /// root with `None`. Descendants of `owner` move along. Only clients that
+    /// are not limited to their own records may change the hierarchy.
+    pub async fn set_parent(&self, owner: &str, parent: Option<&str>) -> Result<(), Error> {
+        if self.write_scope().is_some() {
+            return Err(Error::Unauthorized {
+                table: OWNER_TREE.to_string(),
+                id: owner.to_string(),
+            });
+        }
+
+        // the ancestors are read and checked in the same transaction as the
+        // move, so concurrent moves cannot create a cycle
+        let mut response = self
+            .client
+            .query("BEGIN TRANSACTION;")
+            .query("LET $ancestors = IF $parent THEN array::append(array::flatten(SELECT VALUE ancestors FROM type::thing($table, $parent)), $parent) ELSE [] END;")
+            .query("IF $ancestors CONTAINS $owner { THROW 'cycle' };")
+            .query("UPSERT type::thing($table, $owner) SET name = $owner, parent = $parent, ancestors = $ancestors;")
+            .query("FOR $node IN (SELECT * FROM type::table($table) WHERE ancestors CONTAINS $owner) { UPDATE $node.id SET ancestors = array::concat($ancestors, array::slice($node.ancestors, array::find_index($node.ancestors, $owner))) };")
+            .query("COMMIT TRANSACTION;")
+            .bind(("table", OWNER_TREE))
+            .bind(("owner", owner.to_string()))
+            .bind(("parent", parent.map(str::to_string)))
+            .await?;
+        match aborted(&mut response) {
+            Some((1, surrealdb::Error::Db(Db::Thrown(_)))) => Err(Error::validation(&format!(
+                "{} cannot be placed below itself",
+                owner
+            ))),
+            Some((_, err)) => Err(err.into()),
+            None => Ok(()),
+        }
     }
+
+    /// All owners below `owner` in the owner hierarchy. Clients limited to
+    /// their own records may only list owners they act for or, with
+    /// `hierarchy`, their descendants.
+    pub async fn descendants(&self, owner: &str) -> Result<Vec<String>, Error> {
+        if let Some(owners) = self.read_scope() {
+            let visible = owners.iter().any(|scoped| scoped == owner)
+                || (self.hierarchical
+                    && self
+                        .ancestors(owner)
+                        .await?
+                        .iter()
+                        .any(|ancestor| owners.contains(ancestor)));
+            if !visible {
+                return Err(Error::Unauthorized {
+                    table: OWNER_TREE.to_string(),
+                    id: owner.to_string(),
+                });
+            }
+        }
+        retrying(self.retrier().as_ref(), || async {
+            Ok(self
+                .client
+                .query("SELECT VALUE name FROM type::table($table) WHERE ancestors CONTAINS $owner ORDER BY name;")
+                .bind(("table", OWNER_TREE))
+                .bind(("owner", owner.to_string()))
+                .await?
+                .take(0)?)
+        })
+        .await
+    }
+
+    async fn ancestors(&self, owner: &str) -> Result<Vec<String>, Error> {
+        retrying(self.retrier().as_ref(), || async {
+            let ancestors: Vec<Vec<String>> = self
+                .client
+                .query("SELECT VALUE ancestors FROM type::thing($table, $owner);")
+                .bind(("table", OWNER_TREE))
+                .bind(("owner", owner.to_string()))
+                .await?
+                .take(0)?;
+            Ok(ancestors.into_iter().flatten().collect())
+        })
+        .await
+    }
+}
+
+/// Awaits `writes` concurrently and concatenates the records they return.
+async fn join_writes<T, F>(writes: impl IntoIterator<Item = F>) -> Result<Vec<T>, Error>
+where
+    F: Future<Output = Result<Vec<T>, Error>>,
+{
+    join_all(writes)
+        .await
+        .into_iter()
+        .try_fold(Vec::new(), |mut result, records| {
+            result.extend(records?);
+            Ok(result)
+        })
 }
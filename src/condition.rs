@@ -0,0 +1,154 @@
+use crate::{Bindings, Error};
+
+use std::ops::Not;
+use surrealdb::sql::Value;
+
+pub fn field(name: &str) -> Field {
+    Field(name.to_string())
+}
+
+#[derive(Debug, Clone)]
+pub struct Field(String);
+
+impl Field {
+    fn compare<T: Into<Value>>(self, operator: &'static str, value: T) -> Condition {
+        Condition::Compare {
+            field: self.0,
+            operator,
+            value: value.into(),
+        }
+    }
+
+    pub fn eq<T: Into<Value>>(self, value: T) -> Condition {
+        self.compare("==", value)
+    }
+
+    pub fn ne<T: Into<Value>>(self, value: T) -> Condition {
+        self.compare("!=", value)
+    }
+
+    pub fn gt<T: Into<Value>>(self, value: T) -> Condition {
+        self.compare(">", value)
+    }
+
+    pub fn gte<T: Into<Value>>(self, value: T) -> Condition {
+        self.compare(">=", value)
+    }
+
+    pub fn lt<T: Into<Value>>(self, value: T) -> Condition {
+        self.compare("<", value)
+    }
+
+    pub fn lte<T: Into<Value>>(self, value: T) -> Condition {
+        self.compare("<=", value)
+    }
+
+    pub fn contains<T: Into<Value>>(self, value: T) -> Condition {
+        self.compare("CONTAINS", value)
+    }
+
+    pub fn contains_any<T: Into<Value>>(self, values: T) -> Condition {
+        self.compare("CONTAINSANY", values)
+    }
+
+    pub fn contains_all<T: Into<Value>>(self, values: T) -> Condition {
+        self.compare("CONTAINSALL", values)
+    }
+
+    pub fn inside<T: Into<Value>>(self, values: T) -> Condition {
+        self.compare("INSIDE", values)
+    }
+
+    pub fn is_none(self) -> Condition {
+        Condition::IsNone(self.0)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Condition {
+    Compare {
+        field: String,
+        operator: &'static str,
+        value: Value,
+    },
+    IsNone(String),
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>),
+    Not(Box<Condition>),
+}
+
+impl Condition {
+    pub fn and(self, other: Condition) -> Self {
+        Self::And(Box::new(self), Box::new(other))
+    }
+
+    pub fn or(self, other: Condition) -> Self {
+        Self::Or(Box::new(self), Box::new(other))
+    }
+
+    /// Fails with `Error::Validation` if a field is not a plain path like
+    /// `content.text`, the only form that is safe to put into a query.
+    pub fn validate(&self) -> Result<(), Error> {
+        match self {
+            Self::Compare { field, .. } | Self::IsNone(field) => validate_field(field),
+            Self::And(left, right) | Self::Or(left, right) => {
+                left.validate()?;
+                right.validate()
+            }
+            Self::Not(condition) => condition.validate(),
+        }
+    }
+
+    pub fn render(&self, bindings: &mut Bindings) -> String {
+        match self {
+            Self::Compare {
+                field,
+                operator,
+                value,
+            } => {
+                let param = bindings.bind(&param_name(field), value.clone());
+                format!("{} {} {}", field, operator, param)
+            }
+            Self::IsNone(field) => format!("{} IS NONE", field),
+            Self::And(left, right) => {
+                format!("({} AND {})", left.render(bindings), right.render(bindings))
+            }
+            Self::Or(left, right) => {
+                format!("({} OR {})", left.render(bindings), right.render(bindings))
+            }
+            Self::Not(condition) => format!("!({})", condition.render(bindings)),
+        }
+    }
+}
+
+impl Not for Condition {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Self::Not(Box::new(self))
+    }
+}
+
+pub(crate) fn is_field_path(field: &str) -> bool {
+    !field.is_empty()
+        && field.split('.').all(|part| {
+            !part.is_empty()
+                && !part.starts_with(|c: char| c.is_ascii_digit())
+                && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        })
+}
+
+pub(crate) fn validate_field(field: &str) -> Result<(), Error> {
+    if is_field_path(field) {
+        Ok(())
+    } else {
+        Err(Error::validation(&format!("invalid field {:?}", field)))
+    }
+}
+
+fn param_name(field: &str) -> String {
+    field
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
@@ -0,0 +1,225 @@
+use crate::{Credentials, Error, RetryPolicy};
+
+use std::time::Duration;
+
+/// Where and how a `Client` connects, see `Client::from_config`.
+#[derive(Debug, Clone)]
+pub struct ClientConfig {
+    host: String,
+    port: u16,
+    tls: bool,
+    namespace: String,
+    database: String,
+    credentials: Option<Credentials>,
+    connect_timeout: Option<Duration>,
+    retry: Option<RetryPolicy>,
+}
+
+impl ClientConfig {
+    pub fn new(host: &str, port: u16, namespace: &str, database: &str) -> Self {
+        Self {
+            host: host.to_string(),
+            port,
+            tls: false,
+            namespace: namespace.to_string(),
+            database: database.to_string(),
+            credentials: None,
+            connect_timeout: None,
+            retry: None,
+        }
+    }
+
+    /// Parses `ws://user:pass@host:port/ns/db` or `wss://...`. The user is
+    /// signed in as Root, the port defaults to 8000 for `ws` and 443 for `wss`.
+    pub fn from_url(url: &str) -> Result<Self, Error> {
+        let invalid = |reason: &str| Error::validation(&format!("invalid url: {}", reason));
+        let (scheme, rest) = url
+            .split_once("://")
+            .ok_or_else(|| invalid("missing scheme"))?;
+        let tls = match scheme {
+            "ws" => false,
+            "wss" => true,
+            _ => return Err(invalid("scheme must be ws or wss")),
+        };
+        let (authority, path) = rest
+            .split_once('/')
+            .ok_or_else(|| invalid("missing namespace and database"))?;
+        let (user, address) = match authority.rsplit_once('@') {
+            Some((user, address)) => (Some(user), address),
+            None => (None, authority),
+        };
+
+        let (host, port) = match address.rsplit_once(':') {
+            Some((host, port)) if !port.contains(']') => (
+                host,
+                port.parse::<u16>().map_err(|_| invalid("invalid port"))?,
+            ),
+            _ => (address, if tls { 443 } else { 8000 }),
+        };
+        if host.is_empty() {
+            return Err(invalid("missing host"));
+        }
+
+        let segments = path
+            .trim_end_matches('/')
+            .split('/')
+            .map(decode)
+            .collect::<Result<Vec<String>, Error>>()?;
+        let (namespace, database) = match segments.as_slice() {
+            [namespace, database] if !namespace.is_empty() && !database.is_empty() => {
+                (namespace, database)
+            }
+            _ => return Err(invalid("path must be /namespace/database")),
+        };
+
+        let mut config = Self::new(host, port, namespace, database).with_tls(tls);
+        if let Some(user) = user {
+            let (username, password) = user
+                .split_once(':')
+                .ok_or_else(|| invalid("missing password"))?;
+            config =
+                config.with_credentials(Credentials::root(&decode(username)?, &decode(password)?));
+        }
+        Ok(config)
+    }
+
+    /// Reads `SURREAL_URL` (see `from_url`) or `SURREAL_HOST`, `SURREAL_PORT`,
+    /// `SURREAL_NS`, `SURREAL_DB`, `SURREAL_USER`, `SURREAL_PASS` and
+    /// `SURREAL_TLS`, plus `SURREAL_CONNECT_TIMEOUT` in seconds and
+    /// `SURREAL_RETRIES` for a default `RetryPolicy` with that many retries.
+    pub fn from_env() -> Result<Self, Error> {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    pub(crate) fn from_vars<F: Fn(&str) -> Option<String>>(var: F) -> Result<Self, Error> {
+        let required = |name: &str| {
+            var(name).ok_or_else(|| Error::validation(&format!("{} is not set", name)))
+        };
+
+        let mut config = match var("SURREAL_URL") {
+            Some(url) => Self::from_url(&url)?,
+            None => {
+                let tls = match var("SURREAL_TLS").as_deref() {
+                    None | Some("false") | Some("0") => false,
+                    Some("true") | Some("1") => true,
+                    Some(_) => return Err(Error::validation("SURREAL_TLS must be true or false")),
+                };
+                let port = match var("SURREAL_PORT") {
+                    Some(port) => port
+                        .parse()
+                        .map_err(|_| Error::validation("SURREAL_PORT is not a port"))?,
+                    None if tls => 443,
+                    None => 8000,
+                };
+                let config = Self::new(
+                    &required("SURREAL_HOST")?,
+                    port,
+                    &required("SURREAL_NS")?,
+                    &required("SURREAL_DB")?,
+                )
+                .with_tls(tls);
+                match var("SURREAL_USER") {
+                    Some(username) => config
+                        .with_credentials(Credentials::root(&username, &required("SURREAL_PASS")?)),
+                    None => config,
+                }
+            }
+        };
+
+        if let Some(timeout) = var("SURREAL_CONNECT_TIMEOUT") {
+            let timeout = timeout
+                .parse::<f64>()
+                .ok()
+                .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+                .ok_or_else(|| {
+                    Error::validation("SURREAL_CONNECT_TIMEOUT is not a number of seconds")
+                })?;
+            config = config.with_connect_timeout(timeout);
+        }
+        if let Some(retries) = var("SURREAL_RETRIES") {
+            let retries = retries
+                .parse()
+                .map_err(|_| Error::validation("SURREAL_RETRIES is not a number"))?;
+            config = config.with_retry(RetryPolicy::new(retries));
+        }
+        Ok(config)
+    }
+
+    /// Connects with `wss` instead of `ws`.
+    pub fn with_tls(mut self, tls: bool) -> Self {
+        self.tls = tls;
+        self
+    }
+
+    pub fn with_credentials(mut self, credentials: Credentials) -> Self {
+        self.credentials = Some(credentials);
+        self
+    }
+
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Retries connecting and, once connected, failed reads as described in
+    /// `Client::retry`.
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    pub fn endpoint(&self) -> String {
+        let scheme = if self.tls { "wss" } else { "ws" };
+        format!("{}://{}:{}", scheme, self.host, self.port)
+    }
+
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn is_tls(&self) -> bool {
+        self.tls
+    }
+
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    pub fn database(&self) -> &str {
+        &self.database
+    }
+
+    pub fn credentials(&self) -> Option<&Credentials> {
+        self.credentials.as_ref()
+    }
+
+    pub fn connect_timeout(&self) -> Option<Duration> {
+        self.connect_timeout
+    }
+
+    pub fn retry(&self) -> Option<&RetryPolicy> {
+        self.retry.as_ref()
+    }
+}
+
+fn decode(value: &str) -> Result<String, Error> {
+    let invalid = || Error::validation("invalid url: bad percent encoding");
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%' {
+            let hex = value.get(index + 1..index + 3).ok_or_else(invalid)?;
+            decoded.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+            index += 3;
+        } else {
+            decoded.push(bytes[index]);
+            index += 1;
+        }
+    }
+    String::from_utf8(decoded).map_err(|_| invalid())
+}
@@ -0,0 +1,63 @@
+use crate::Error;
+
+use surrealdb::sql::{self, Array, Id, Value};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cursor(Vec<Value>);
+
+impl Cursor {
+    pub fn new(values: Vec<Value>) -> Self {
+        Self(values)
+    }
+
+    pub fn values(&self) -> &[Value] {
+        &self.0
+    }
+
+    pub fn encode(&self) -> String {
+        Array::from(self.0.clone())
+            .to_string()
+            .bytes()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self, Error> {
+        let invalid = || Error::validation("invalid cursor");
+        if cursor.len() % 2 != 0 {
+            return Err(invalid());
+        }
+        let bytes = (0..cursor.len())
+            .step_by(2)
+            .map(|index| u8::from_str_radix(cursor.get(index..index + 2)?, 16).ok())
+            .collect::<Option<Vec<u8>>>()
+            .ok_or_else(invalid)?;
+        let text = String::from_utf8(bytes).map_err(|_| invalid())?;
+        match sql::value(&text).map_err(|_| invalid())? {
+            Value::Array(values) if values.iter().all(is_literal) => Ok(Self(values.0)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+fn is_literal(value: &Value) -> bool {
+    match value {
+        Value::None
+        | Value::Null
+        | Value::Bool(_)
+        | Value::Number(_)
+        | Value::Strand(_)
+        | Value::Duration(_)
+        | Value::Datetime(_)
+        | Value::Uuid(_) => true,
+        Value::Array(values) => values.iter().all(is_literal),
+        Value::Object(object) => object.values().all(is_literal),
+        Value::Thing(thing) => match &thing.id {
+            Id::Number(_) | Id::String(_) | Id::Uuid(_) => true,
+            Id::Array(values) => values.iter().all(is_literal),
+            Id::Object(object) => object.values().all(is_literal),
+            _ => false,
+        },
+        _ => false,
+    }
+}
@@ -1,27 +1,126 @@
 use std::error;
 use std::fmt;
-use surrealdb;
+use std::io;
+use surrealdb::error::{Api, Db};
+use surrealdb::Response;
 
 #[derive(Debug)]
 pub enum Error {
-    Surreal(String),
+    Surreal(Box<surrealdb::Error>),
+    Connection(Box<surrealdb::Error>),
+    Serialization(serde_json::Error),
+    NotFound {
+        table: String,
+        id: Option<String>,
+    },
+    Unauthorized {
+        table: String,
+        id: String,
+    },
+    Conflict {
+        table: String,
+        id: String,
+        source: Option<Box<surrealdb::Error>>,
+    },
+    MissingTable,
+    MissingId {
+        table: String,
+    },
+    IdMismatch {
+        table: String,
+        id: String,
+    },
+    Validation(String),
+    UnexpectedResponse,
+    PoisonedTransaction,
     FancySurreal(String),
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::Surreal(msg) => write!(f, "Surreal({})", msg),
+            Self::Surreal(err) => write!(f, "Surreal({})", err),
+            Self::Connection(err) => write!(f, "Connection({})", err),
+            Self::Serialization(err) => write!(f, "Serialization({})", err),
+            Self::NotFound {
+                table,
+                id: Some(id),
+            } => write!(f, "NotFound({}:{})", table, id),
+            Self::NotFound { table, id: None } => write!(f, "NotFound({})", table),
+            Self::Unauthorized { table, id } => write!(f, "Unauthorized({}:{})", table, id),
+            Self::Conflict { table, id, .. } => write!(f, "Conflict({}:{})", table, id),
+            Self::MissingTable => write!(f, "MissingTable"),
+            Self::MissingId { table } => write!(f, "MissingId({})", table),
+            Self::IdMismatch { table, id } => write!(f, "IdMismatch({}:{})", table, id),
+            Self::Validation(msg) => write!(f, "Validation({})", msg),
+            Self::UnexpectedResponse => write!(f, "UnexpectedResponse"),
+            Self::PoisonedTransaction => write!(f, "PoisonedTransaction"),
             Self::FancySurreal(msg) => write!(f, "FancySurreal({})", msg),
         }
     }
 }
 
-impl error::Error for Error {}
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Surreal(err)
+            | Self::Connection(err)
+            | Self::Conflict {
+                source: Some(err), ..
+            } => Some(err.as_ref()),
+            Self::Serialization(err) => Some(err),
+            _ => None,
+        }
+    }
+}
 
 impl From<surrealdb::Error> for Error {
     fn from(err: surrealdb::Error) -> Self {
-        Self::Surreal(err.to_string())
+        match err {
+            surrealdb::Error::Db(Db::RecordExists { ref thing }) => Self::Conflict {
+                table: thing.tb.clone(),
+                id: thing.id.to_raw(),
+                source: Some(Box::new(err)),
+            },
+            // Only transport failures, which a retry may get past. A bad
+            // scheme or url stays a `Surreal` error.
+            surrealdb::Error::Api(Api::Ws(_) | Api::Http(_) | Api::ConnectionUninitialised) => {
+                Self::Connection(Box::new(err))
+            }
+            // Requests pending when the websocket drops fail with a reset
+            // connection, requests to a router that shut down with a closed
+            // channel.
+            surrealdb::Error::Db(Db::Io(ref io)) if io.kind() == io::ErrorKind::ConnectionReset => {
+                Self::Connection(Box::new(err))
+            }
+            surrealdb::Error::Api(Api::InternalError(ref message))
+                if message.ends_with("closed channel") =>
+            {
+                Self::Connection(Box::new(err))
+            }
+            err => Self::Surreal(Box::new(err)),
+        }
+    }
+}
+
+/// The error of the statement that aborted the transaction in `response`
+/// and its index, leaving out the statements that were cancelled with it.
+pub(crate) fn aborted(response: &mut Response) -> Option<(usize, surrealdb::Error)> {
+    response
+        .take_errors()
+        .into_iter()
+        .filter(|(_, err)| {
+            !matches!(
+                err,
+                surrealdb::Error::Db(Db::QueryCancelled | Db::QueryNotExecuted)
+            )
+        })
+        .min_by_key(|(index, _)| *index)
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Serialization(err)
     }
 }
 
@@ -29,4 +128,8 @@ impl Error {
     pub fn new(msg: &str) -> Self {
         Self::FancySurreal(msg.to_string())
     }
+
+    pub fn validation(msg: &str) -> Self {
+        Self::Validation(msg.to_string())
+    }
 }
@@ -0,0 +1,39 @@
+use crate::Bindings;
+
+/// Table holding the owner hierarchy. Every node stores its `parent` and the
+/// `ancestors` up to the root, so descendants can be found without recursion.
+pub(crate) const OWNER_TREE: &str = "owner_tree";
+
+/// Renders the condition limiting records to `owners`, either through the
+/// `owner` field or one of the access lists in `shared` (e.g. `readers`).
+/// With `hierarchical` the owners' descendants in `OWNER_TREE` are included.
+pub(crate) fn owner_condition(
+    bindings: &mut Bindings,
+    owners: Vec<String>,
+    shared: &[&str],
+    hierarchical: bool,
+) -> String {
+    if owners.len() == 1 && !hierarchical {
+        let owner = bindings.bind("owner", owners.into_iter().next().unwrap());
+        let mut condition = format!("owner == {}", owner);
+        for list in shared {
+            condition = format!("{} OR {} IN {}", condition, owner, list);
+        }
+        return format!("({})", condition);
+    }
+
+    let owners = bindings.bind("owners", owners);
+    let owners = if hierarchical {
+        format!(
+            "array::union({0}, (SELECT VALUE name FROM {1} WHERE ancestors CONTAINSANY {0}))",
+            owners, OWNER_TREE
+        )
+    } else {
+        owners
+    };
+    let mut condition = format!("owner IN {}", owners);
+    for list in shared {
+        condition = format!("{} OR {} CONTAINSANY {}", condition, list, owners);
+    }
+    format!("({})", condition)
+}
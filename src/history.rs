@@ -0,0 +1,74 @@
+use crate::record::content_id;
+use crate::{Databasable, Error};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Datetime;
+use surrealdb::RecordId;
+
+/// A previous state of a record, kept in `<table>_history`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Revision<T: Databasable + Serialize> {
+    id: RecordId,
+    record: RecordId,
+    owner: Option<String>,
+    content: T,
+    created_at: Option<Datetime>,
+    updated_at: Option<Datetime>,
+    version: Option<u64>,
+    deleted_at: Option<Datetime>,
+    changed_by: Option<String>,
+    changed_at: Datetime,
+}
+
+impl<'de, T: Databasable + Serialize + Deserialize<'de>> Revision<T> {
+    pub fn id(&self) -> &RecordId {
+        &self.id
+    }
+
+    pub fn owner(&self) -> Option<&str> {
+        self.owner.as_deref()
+    }
+
+    pub fn version(&self) -> Option<u64> {
+        self.version
+    }
+
+    pub fn updated_at(&self) -> Option<DateTime<Utc>> {
+        self.updated_at.as_ref().map(|updated_at| updated_at.0)
+    }
+
+    pub fn deleted_at(&self) -> Option<DateTime<Utc>> {
+        self.deleted_at.as_ref().map(|deleted_at| deleted_at.0)
+    }
+
+    pub fn changed_by(&self) -> Option<&str> {
+        self.changed_by.as_deref()
+    }
+
+    pub fn changed_at(&self) -> DateTime<Utc> {
+        self.changed_at.0
+    }
+
+    /// The content as it was before the change, with the id of the record.
+    pub fn content(self) -> Result<T, Error> {
+        let mut content = self.content;
+        content.set_id(Some(content_id::<T>(self.record)?));
+        Ok(content)
+    }
+}
+
+pub(crate) fn history_table(table: &str) -> String {
+    format!("{}_history", table)
+}
+
+/// Copies every record matched by `source` (e.g. `$id` or
+/// `table WHERE ...`) into the history table before it gets changed.
+pub(crate) fn history_str(table: &str, source: &str, changed_by: &str) -> String {
+    format!(
+        "FOR $previous IN (SELECT * FROM {source}) {{ CREATE {history} SET record = $previous.id, owner = $previous.owner, content = $previous.content, created_at = $previous.created_at, updated_at = $previous.updated_at, version = $previous.version, deleted_at = $previous.deleted_at, changed_by = {changed_by}, changed_at = time::now() }};\n",
+        source = source,
+        history = history_table(table),
+        changed_by = changed_by,
+    )
+}
@@ -1,18 +1,47 @@
+extern crate self as fancy_surreal;
+
+mod auth;
+mod bindings;
 mod change;
 mod client;
+mod condition;
+mod config;
+mod cursor;
 mod error;
+mod hierarchy;
+mod history;
+mod live;
+mod order;
+mod page;
+mod principal;
 mod record;
+mod retry;
+pub mod schema;
 mod select;
 #[cfg(test)]
 mod tests;
 mod traits;
+mod transaction;
 
+pub use auth::Credentials;
+pub use bindings::Bindings;
 pub use change::Change;
 pub use change::Operator as ChangeOperator;
 pub use client::Client;
+pub use condition::{field, Condition, Field};
+pub use config::ClientConfig;
 pub use error::Error;
+pub use fancy_surreal_derive::Databasable;
+pub use history::Revision;
+pub use live::Notification;
+pub use order::{Direction, Order};
+pub use page::{CursorPage, Page};
+pub use principal::{Permission, Principal, Role};
 pub use record::Record;
+pub use retry::RetryPolicy;
 pub use select::Select;
 pub use surrealdb::sql::Id;
 pub use surrealdb::RecordId;
-pub use traits::Databasable;
+pub use traits::{Databasable, RecordKey};
+pub use transaction::Transaction;
+pub use ulid::Ulid;
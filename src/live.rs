@@ -0,0 +1,46 @@
+use crate::{Databasable, Error, Record};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use surrealdb::Action;
+
+/// A change pushed by `Select::live`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Notification<T> {
+    Create(T),
+    Update(T),
+    Delete(T),
+}
+
+impl<T> Notification<T> {
+    pub fn content(&self) -> &T {
+        match self {
+            Self::Create(content) | Self::Update(content) | Self::Delete(content) => content,
+        }
+    }
+
+    pub fn into_content(self) -> T {
+        match self {
+            Self::Create(content) | Self::Update(content) | Self::Delete(content) => content,
+        }
+    }
+}
+
+pub(crate) fn notification<T: Databasable + Serialize + DeserializeOwned>(
+    notification: surrealdb::Result<surrealdb::Notification<Record<T>>>,
+) -> Option<Result<Notification<T>, Error>> {
+    let notification = match notification {
+        Ok(notification) => notification,
+        Err(err) => return Some(Err(err.into())),
+    };
+    let content = match notification.data.content() {
+        Ok(content) => content,
+        Err(err) => return Some(Err(err)),
+    };
+    match notification.action {
+        Action::Create => Some(Ok(Notification::Create(content))),
+        Action::Update => Some(Ok(Notification::Update(content))),
+        Action::Delete => Some(Ok(Notification::Delete(content))),
+        _ => None,
+    }
+}
@@ -0,0 +1,120 @@
+use crate::condition::is_field_path;
+use crate::Error;
+
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Asc,
+    Desc,
+}
+
+impl Direction {
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            Self::Asc => "ASC",
+            Self::Desc => "DESC",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Order {
+    field: String,
+    direction: Direction,
+    collate: bool,
+    numeric: bool,
+}
+
+impl Order {
+    pub fn new(field: &str, direction: Direction) -> Self {
+        Self {
+            field: field.to_string(),
+            direction,
+            collate: false,
+            numeric: false,
+        }
+    }
+
+    pub fn asc(field: &str) -> Self {
+        Self::new(field, Direction::Asc)
+    }
+
+    pub fn desc(field: &str) -> Self {
+        Self::new(field, Direction::Desc)
+    }
+
+    pub fn collate(mut self) -> Self {
+        self.collate = true;
+        self
+    }
+
+    pub fn numeric(mut self) -> Self {
+        self.numeric = true;
+        self
+    }
+
+    pub fn field(&self) -> &str {
+        &self.field
+    }
+
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    /// Whether the order compares values as `<` and `>` do, which keyset
+    /// pagination relies on.
+    pub(crate) fn is_plain(&self) -> bool {
+        !self.collate && !self.numeric
+    }
+
+    pub fn validate(&self) -> Result<(), Error> {
+        if is_field_path(&self.field) {
+            Ok(())
+        } else {
+            Err(Error::validation(&format!(
+                "invalid order field {:?}",
+                self.field
+            )))
+        }
+    }
+}
+
+impl fmt::Display for Order {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.field)?;
+        if self.collate {
+            write!(f, " COLLATE")?;
+        }
+        if self.numeric {
+            write!(f, " NUMERIC")?;
+        }
+        write!(f, " {}", self.direction.to_str())
+    }
+}
+
+/// Parses the `field [COLLATE] [NUMERIC] [ASC|DESC]` form of an `ORDER BY`
+/// clause, as accepted by `Select::order_by`.
+impl FromStr for Order {
+    type Err = Error;
+
+    fn from_str(clause: &str) -> Result<Self, Error> {
+        let invalid = || Error::validation(&format!("invalid order {:?}", clause));
+        let mut words = clause.split_whitespace();
+        let mut order = Self::asc(words.next().ok_or_else(invalid)?);
+        let mut direction = None;
+        for word in words {
+            match word.to_ascii_uppercase().as_str() {
+                "COLLATE" if !order.collate && direction.is_none() => order = order.collate(),
+                "NUMERIC" if !order.numeric && direction.is_none() => order = order.numeric(),
+                "ASC" if direction.is_none() => direction = Some(Direction::Asc),
+                "DESC" if direction.is_none() => direction = Some(Direction::Desc),
+                _ => return Err(invalid()),
+            }
+        }
+        order.direction = direction.unwrap_or(Direction::Asc);
+        order.validate()?;
+        Ok(order)
+    }
+}
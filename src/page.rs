@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: usize,
+    pub start: usize,
+    pub limit: Option<usize>,
+    pub next: Option<usize>,
+    pub previous: Option<usize>,
+}
+
+impl<T> Page<T> {
+    /// Pages that make no progress, e.g. with a limit of 0, have neither
+    /// `next` nor a `previous` that points back to `start`.
+    pub fn new(items: Vec<T>, total: usize, start: usize, limit: Option<usize>) -> Self {
+        let end = start + items.len();
+        Self {
+            next: if end > start && end < total {
+                Some(end)
+            } else {
+                None
+            },
+            previous: if start > 0 {
+                Some(start.saturating_sub(limit.filter(|limit| *limit > 0).unwrap_or(start)))
+            } else {
+                None
+            },
+            items,
+            total,
+            start,
+            limit,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CursorPage<T> {
+    pub items: Vec<T>,
+    pub next: Option<String>,
+}
@@ -0,0 +1,39 @@
+/// Who a `Client` acts as. Privilege comes from the variant, never from the
+/// value of an owner string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Principal {
+    /// Sees and changes every record.
+    Admin,
+    /// A backend process identified by name, with the same access as `Admin`.
+    Service(String),
+    /// Limited to records it owns.
+    User(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Admin,
+    Service,
+    User,
+}
+
+impl Principal {
+    pub fn role(&self) -> Role {
+        match self {
+            Self::Admin => Role::Admin,
+            Self::Service(_) => Role::Service,
+            Self::User(_) => Role::User,
+        }
+    }
+
+    pub fn is_privileged(&self) -> bool {
+        matches!(self.role(), Role::Admin | Role::Service)
+    }
+}
+
+/// What a user a record is shared with may do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    Read,
+    Edit,
+}
@@ -1,30 +1,157 @@
-use crate::Databasable;
+use crate::{Bindings, Databasable, Error, RecordKey};
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use surrealdb::RecordId;
+use surrealdb::sql::{Datetime, Id};
+use surrealdb::{RecordId, RecordIdKey};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Record<T: Databasable + Serialize> {
     id: Option<RecordId>,
     owner: Option<String>,
     content: T,
+    created_at: Option<Datetime>,
+    updated_at: Option<Datetime>,
+    version: Option<u64>,
+    deleted_at: Option<Datetime>,
+    #[serde(default)]
+    readers: Vec<String>,
+    #[serde(default)]
+    editors: Vec<String>,
 }
 
 impl<'de, T: Databasable + Serialize + Deserialize<'de>> Record<T> {
+    /// A record for `content`, its timestamps are set by the server when it
+    /// is written.
     pub fn new(mut content: T, table: String, owner: Option<String>) -> Self {
         Record {
             id: content.get_id().map(|id| {
                 content.set_id(None);
-                RecordId::from_table_key(table, id.to_string())
+                record_id(&table, id.into_id())
             }),
             owner,
             content,
+            created_at: None,
+            updated_at: None,
+            version: Some(1),
+            deleted_at: None,
+            readers: vec![],
+            editors: vec![],
         }
     }
 
-    pub fn content(self) -> T {
+    pub fn created_at(&self) -> Option<DateTime<Utc>> {
+        self.created_at.as_ref().map(|created_at| created_at.0)
+    }
+
+    pub fn updated_at(&self) -> Option<DateTime<Utc>> {
+        self.updated_at.as_ref().map(|updated_at| updated_at.0)
+    }
+
+    pub fn version(&self) -> Option<u64> {
+        self.version
+    }
+
+    pub fn deleted_at(&self) -> Option<DateTime<Utc>> {
+        self.deleted_at.as_ref().map(|deleted_at| deleted_at.0)
+    }
+
+    pub fn readers(&self) -> &[String] {
+        &self.readers
+    }
+
+    pub fn editors(&self) -> &[String] {
+        &self.editors
+    }
+
+    /// The content with the id of the record, failing with
+    /// `Error::IdMismatch` if the stored id is not a `T::Id`.
+    pub fn content(self) -> Result<T, Error> {
         let mut content = self.content;
-        content.set_id(self.id.map(|id| id.key().to_string()));
-        content
+        content.set_id(self.id.map(content_id::<T>).transpose()?);
+        Ok(content)
+    }
+
+    /// Creates `target` with owner and content, setting both timestamps to
+    /// the same server time.
+    pub(crate) fn create_str(self, bindings: &mut Bindings, target: &str) -> Result<String, Error>
+    where
+        T: 'static,
+    {
+        let owner = bindings.bind("owner", self.owner);
+        let content = bindings.bind_serialize("content", self.content)?;
+        Ok(format!(
+            "CREATE {} SET owner = {}, content = {}, created_at = time::now(), updated_at = created_at, version = 1, readers = [], editors = [];",
+            target, owner, content
+        ))
+    }
+
+    /// Writes owner and content to `target`, keeping `created_at` of an
+    /// existing record and refreshing `updated_at` and `version` on the server.
+    pub(crate) fn upsert_str(self, bindings: &mut Bindings, target: &str) -> Result<String, Error>
+    where
+        T: 'static,
+    {
+        Ok(format!(
+            "UPSERT {} SET {};",
+            target,
+            self.set_str(bindings)?
+        ))
+    }
+
+    /// Like `upsert_str`, but only touches the record if its stored version
+    /// still equals the bound `version`.
+    pub(crate) fn update_if_version_str(
+        self,
+        bindings: &mut Bindings,
+        target: &str,
+        version: &str,
+    ) -> Result<String, Error>
+    where
+        T: 'static,
+    {
+        let set = self.set_str(bindings)?;
+        Ok(format!(
+            "UPDATE {} SET {} WHERE version == {};",
+            target, set, version
+        ))
+    }
+
+    fn set_str(self, bindings: &mut Bindings) -> Result<String, Error>
+    where
+        T: 'static,
+    {
+        let owner = bindings.bind("owner", self.owner);
+        let content = bindings.bind_serialize("content", self.content)?;
+        Ok(format!(
+            "owner = owner ?? {}, content = {}, created_at = created_at ?? time::now(), updated_at = time::now(), version = (version ?? 0) + 1",
+            owner, content
+        ))
     }
 }
+
+/// Inserts `records` into `table` with both timestamps set to the server
+/// time.
+pub(crate) fn insert_str<T: Databasable + Serialize + 'static>(
+    bindings: &mut Bindings,
+    table: &str,
+    records: Vec<Record<T>>,
+) -> Result<String, Error> {
+    let records = bindings.bind_serialize("records", records)?;
+    Ok(format!(
+        "INSERT INTO {} (SELECT *, created_at AS updated_at FROM (SELECT *, time::now() AS created_at FROM {}));",
+        table, records
+    ))
+}
+
+pub(crate) fn content_id<T: Databasable>(id: RecordId) -> Result<T::Id, Error> {
+    let key = id.key().clone().into_inner();
+    T::Id::from_id(key.clone()).ok_or_else(|| Error::IdMismatch {
+        table: id.table().to_string(),
+        id: key.to_raw(),
+    })
+}
+
+pub(crate) fn record_id(table: &str, id: Id) -> RecordId {
+    RecordId::from_table_key(table, RecordIdKey::from_inner(id))
+}
@@ -0,0 +1,145 @@
+use crate::{Credentials, Error};
+
+use std::future::Future;
+use std::time::Duration;
+use surrealdb::engine::any::Any;
+use surrealdb::Surreal;
+
+/// How often and after how long a `Client` retries operations that failed
+/// with `Error::Connection`, see `Client::retry`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    max_retries: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+            multiplier: 2.0,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    pub fn with_max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// Factor the backoff grows by with every retry, at least 1.
+    pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    pub fn max_retries(&self) -> u32 {
+        self.max_retries
+    }
+
+    /// Time to wait before the retry `attempt`, counting from 0.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let multiplier = if self.multiplier.is_finite() {
+            self.multiplier.max(1.0)
+        } else {
+            1.0
+        };
+        let backoff = self.initial_backoff.as_secs_f64()
+            * multiplier.powi(attempt.min(i32::MAX as u32) as i32);
+        Duration::try_from_secs_f64(backoff)
+            .ok()
+            .filter(|backoff| *backoff < self.max_backoff)
+            .unwrap_or(self.max_backoff)
+    }
+}
+
+/// What a client selected and signed in with, to restore it after the
+/// connection dropped.
+#[derive(Debug, Clone)]
+pub(crate) struct Session {
+    client: Surreal<Any>,
+    namespace: String,
+    database: String,
+    credentials: Option<Credentials>,
+}
+
+impl Session {
+    pub(crate) fn new(
+        client: Surreal<Any>,
+        namespace: &str,
+        database: &str,
+        credentials: Option<Credentials>,
+    ) -> Self {
+        Self {
+            client,
+            namespace: namespace.to_string(),
+            database: database.to_string(),
+            credentials: credentials.map(Credentials::into_signin),
+        }
+    }
+
+    async fn restore(&self) -> Result<(), Error> {
+        if let Some(credentials) = &self.credentials {
+            credentials
+                .authenticate(&self.client, &self.namespace, &self.database)
+                .await?;
+        }
+        self.client
+            .use_ns(&self.namespace)
+            .use_db(&self.database)
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Retry {
+    pub(crate) policy: RetryPolicy,
+    pub(crate) session: Option<Session>,
+}
+
+/// Runs `f` until it succeeds, fails with another error than
+/// `Error::Connection` or `retry` runs out of attempts. The session is
+/// restored before every retry, an attempt whose session cannot be restored
+/// fails with that error.
+pub(crate) async fn retrying<F, Fut, R>(retry: Option<&Retry>, mut f: F) -> Result<R, Error>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<R, Error>>,
+{
+    let mut result = f().await;
+    let Some(retry) = retry else {
+        return result;
+    };
+    for attempt in 0..retry.policy.max_retries {
+        if !matches!(result, Err(Error::Connection(_))) {
+            break;
+        }
+        tokio::time::sleep(retry.policy.backoff(attempt)).await;
+        result = match &retry.session {
+            Some(session) => match session.restore().await {
+                Ok(()) => f().await,
+                Err(err) => Err(err),
+            },
+            None => f().await,
+        };
+    }
+    result
+}
@@ -0,0 +1,53 @@
+use crate::client::IDEMPOTENCY;
+use crate::condition::is_field_path;
+use crate::Error;
+
+/// Statements defining `table` with permissions that only let record users
+/// reach their own records and those shared with them, so tenant isolation
+/// also holds for connections that bypass this crate. Owners are compared
+/// with the string form of the record id in `$auth` (e.g. `user:alice`),
+/// which is what `Client::record_user` uses as owner. Editors may change
+/// the content, but only the owner may change `owner`, `readers` and
+/// `editors`. Existing definitions are kept, see `redefine_table`.
+pub fn define_table(table: &str) -> Result<String, Error> {
+    table_statements(table, "IF NOT EXISTS")
+}
+
+/// Like `define_table`, but replaces existing definitions of the table and
+/// its access fields.
+pub fn redefine_table(table: &str) -> Result<String, Error> {
+    table_statements(table, "OVERWRITE")
+}
+
+/// Statements defining the `idempotency` table the keys of
+/// `Client::idempotency_key` are tracked in, so record users only reach
+/// their own keys. Existing definitions are kept.
+pub fn define_idempotency_table() -> String {
+    format!(
+        "DEFINE TABLE IF NOT EXISTS {table} SCHEMALESS PERMISSIONS \
+         FOR select, create, delete WHERE owner = <string> $auth \
+         FOR update NONE;\n\
+         DEFINE INDEX IF NOT EXISTS created_at ON {table} FIELDS created_at;",
+        table = IDEMPOTENCY
+    )
+}
+
+fn table_statements(table: &str, mode: &str) -> Result<String, Error> {
+    if table.contains('.') || !is_field_path(table) {
+        return Err(Error::validation(&format!("invalid table {:?}", table)));
+    }
+    // Field permissions see the document after the change, so the owner is
+    // read from the stored record.
+    Ok(format!(
+        "DEFINE TABLE {mode} {table} SCHEMALESS PERMISSIONS \
+         FOR select WHERE owner = <string> $auth OR <string> $auth IN readers OR <string> $auth IN editors \
+         FOR create WHERE owner = <string> $auth \
+         FOR update WHERE owner = <string> $auth OR <string> $auth IN editors \
+         FOR delete WHERE owner = <string> $auth;\n\
+         DEFINE FIELD {mode} owner ON {table} PERMISSIONS FOR update WHERE $before = <string> $auth;\n\
+         DEFINE FIELD {mode} readers ON {table} PERMISSIONS FOR update WHERE (SELECT VALUE owner FROM ONLY $parent.id) = <string> $auth;\n\
+         DEFINE FIELD {mode} editors ON {table} PERMISSIONS FOR update WHERE (SELECT VALUE owner FROM ONLY $parent.id) = <string> $auth;",
+        mode = mode,
+        table = table
+    ))
+}
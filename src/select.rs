@@ -1,52 +1,86 @@
-use crate::{Databasable, Error, Record};
+use crate::cursor::Cursor;
+use crate::hierarchy::owner_condition;
+use crate::live;
+use crate::retry::{retrying, Retry};
+use crate::{
+    field, Bindings, Condition, CursorPage, Databasable, Direction, Error, Notification, Order,
+    Page, Record,
+};
 
+use chrono::{DateTime, Utc};
+use futures::{future, Stream, StreamExt};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
-use surrealdb::engine::remote::ws::Client;
+use surrealdb::engine::any::Any;
+use surrealdb::sql::{self, Datetime, Id, Thing, Value};
 use surrealdb::Surreal;
 
+#[derive(Clone)]
 pub struct Select<'a> {
-    client: Surreal<Client>,
+    client: Surreal<Any>,
     table: &'a str,
+    bindings: Bindings,
     fields: String,
     condition: String,
     fetch: String,
     wrapper: Vec<(String, String)>,
-    order_by: String,
+    orders: Vec<Order>,
+    limit: Option<usize>,
+    start: Option<usize>,
+    cursor: Option<Cursor>,
+    exclude_deleted: bool,
+    retry: Option<Retry>,
+    invalid: Option<String>,
 }
 
 impl<'a> Select<'a> {
-    pub fn new(client: Surreal<Client>, table: &'a str, owners: Vec<String>) -> Self {
-        let select = Self {
+    pub fn new(client: Surreal<Any>, table: &'a str, owners: Vec<String>) -> Self {
+        Self::scoped(client, table, owners, false)
+    }
+
+    pub(crate) fn scoped(
+        client: Surreal<Any>,
+        table: &'a str,
+        owners: Vec<String>,
+        hierarchical: bool,
+    ) -> Self {
+        let mut select = Self {
             client,
             table,
+            bindings: Bindings::new(),
             fields: String::new(),
             condition: String::new(),
             fetch: String::new(),
             wrapper: Vec::new(),
-            order_by: String::new(),
+            orders: Vec::new(),
+            limit: None,
+            start: None,
+            cursor: None,
+            exclude_deleted: false,
+            retry: None,
+            invalid: None,
         };
 
-        if owners.contains(&"admin".to_string()) {
-            select
-        } else if owners.len() > 1 {
-            select.condition(&format!(
-                "owner in [{}]",
-                owners
-                    .iter()
-                    .map(|owner| format!("\"{}\"", owner))
-                    .collect::<Vec<String>>()
-                    .join(",")
-            ))
-        } else if owners.len() > 0 {
-            select.condition(&format!("owner == \"{}\"", owners[0]))
-        } else {
-            select
+        if owners.is_empty() {
+            return select;
         }
+        let condition = owner_condition(
+            &mut select.bindings,
+            owners,
+            &["readers", "editors"],
+            hierarchical,
+        );
+        select.and(&condition)
+    }
+
+    /// Adds the raw `condition`, grouped so it cannot widen the others.
+    pub fn condition(self, condition: &str) -> Self {
+        self.and(&format!("({})", condition))
     }
 
-    pub fn condition(mut self, condition: &str) -> Self {
-        if self.condition.len() == 0 {
+    /// Adds `condition`, which must not contain a top level `OR`.
+    fn and(mut self, condition: &str) -> Self {
+        if self.condition.is_empty() {
             self.condition = condition.into();
         } else {
             self.condition = format!("{} AND {}", self.condition, condition);
@@ -54,13 +88,42 @@ impl<'a> Select<'a> {
         self
     }
 
-    pub fn id(self, id: &str) -> Self {
-        let condition = format!("id = {}:{}", self.table, id);
-        self.condition(&condition)
+    /// Adds `condition`. If one of its fields is invalid it is left out and
+    /// the select fails with `Error::Validation` when run.
+    pub fn filter(mut self, condition: Condition) -> Self {
+        if let Err(err) = condition.validate() {
+            self.invalidate(err);
+            return self;
+        }
+        let condition = condition.render(&mut self.bindings);
+        self.and(&condition)
+    }
+
+    pub fn id<K: Into<Id>>(mut self, id: K) -> Self {
+        let id = self
+            .bindings
+            .bind("id", Thing::from((self.table, id.into())));
+        self.and(&format!("id == {}", id))
+    }
+
+    pub(crate) fn exclude_deleted(mut self) -> Self {
+        self.exclude_deleted = true;
+        self
+    }
+
+    pub(crate) fn with_retry(mut self, retry: Option<Retry>) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Includes soft deleted records again.
+    pub fn with_deleted(mut self) -> Self {
+        self.exclude_deleted = false;
+        self
     }
 
     pub fn field(mut self, field: &str) -> Self {
-        if self.fields.len() == 0 {
+        if self.fields.is_empty() {
             self.fields = field.into();
         } else {
             self.fields = format!("{}, {}", self.fields, field);
@@ -97,13 +160,105 @@ impl<'a> Select<'a> {
         ))
     }
 
-    pub fn order_by(mut self, field: &str) -> Self {
-        self.order_by = field.into();
+    /// Sorts by `order` after the orders added before. An invalid or
+    /// repeated field is left out and the select fails with
+    /// `Error::Validation` when run.
+    pub fn order(mut self, order: Order) -> Self {
+        let result = order.validate().and_then(|_| {
+            if self
+                .orders
+                .iter()
+                .any(|other| other.field() == order.field())
+            {
+                Err(Error::validation(&format!(
+                    "duplicate order field {:?}",
+                    order.field()
+                )))
+            } else {
+                Ok(())
+            }
+        });
+        match result {
+            Ok(()) => self.orders.push(order),
+            Err(err) => self.invalidate(err),
+        }
         self
     }
 
+    /// Sorts by `fields`, either a single field or a comma separated list of
+    /// `ORDER BY` clauses like `"date DESC, amount"`.
+    pub fn order_by(mut self, fields: &str) -> Self {
+        for clause in fields.split(',') {
+            match clause.parse::<Order>() {
+                Ok(order) => self = self.order(order),
+                Err(err) => self.invalidate(err),
+            }
+        }
+        self
+    }
+
+    pub fn order_by_desc(self, field: &str) -> Self {
+        self.order(Order::desc(field))
+    }
+
+    pub fn created_after(self, time: DateTime<Utc>) -> Self {
+        self.filter(field("created_at").gt(Datetime::from(time)))
+    }
+
+    pub fn created_before(self, time: DateTime<Utc>) -> Self {
+        self.filter(field("created_at").lt(Datetime::from(time)))
+    }
+
+    pub fn updated_after(self, time: DateTime<Utc>) -> Self {
+        self.filter(field("updated_at").gt(Datetime::from(time)))
+    }
+
+    pub fn updated_before(self, time: DateTime<Utc>) -> Self {
+        self.filter(field("updated_at").lt(Datetime::from(time)))
+    }
+
+    pub fn order_by_created(self, direction: Direction) -> Self {
+        self.order(Order::new("created_at", direction))
+    }
+
+    pub fn order_by_updated(self, direction: Direction) -> Self {
+        self.order(Order::new("updated_at", direction))
+    }
+
+    fn invalidate(&mut self, err: Error) {
+        if let Error::Validation(msg) = err {
+            self.invalid.get_or_insert(msg);
+        }
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        match &self.invalid {
+            Some(msg) => Err(Error::validation(msg)),
+            None => Ok(()),
+        }
+    }
+
+    pub fn bindings(&self) -> &Bindings {
+        &self.bindings
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn start(mut self, start: usize) -> Self {
+        self.start = Some(start);
+        self
+    }
+
+    pub fn after(mut self, cursor: &str) -> Result<Self, Error> {
+        self.cursor = Some(Cursor::decode(cursor)?);
+        Ok(self)
+    }
+
     pub fn query_str(&self) -> String {
-        let fields = if self.fields.len() > 0 {
+        let fields = if !self.fields.is_empty() {
             &self.fields
         } else {
             "*"
@@ -111,16 +266,32 @@ impl<'a> Select<'a> {
 
         let mut query = format!("SELECT {} FROM {}", fields, self.table);
 
-        if self.condition.len() > 0 {
-            query = format!("{} WHERE {}", query, self.condition);
+        if let Some(condition) = self.where_str() {
+            query = format!("{} WHERE {}", query, condition);
         }
 
-        if self.fetch.len() > 0 {
-            query = format!("{} FETCH {}", query, self.fetch);
+        if !self.orders.is_empty() {
+            query = format!(
+                "{} ORDER BY {}",
+                query,
+                self.orders
+                    .iter()
+                    .map(|order| order.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            );
+        }
+
+        if let Some(limit) = self.limit {
+            query = format!("{} LIMIT {}", query, limit);
+        }
+
+        if let Some(start) = self.start {
+            query = format!("{} START {}", query, start);
         }
 
-        if self.order_by.len() > 0 {
-            query = format!("{} ORDER BY {}", query, self.order_by);
+        if !self.fetch.is_empty() {
+            query = format!("{} FETCH {}", query, self.fetch);
         }
 
         for wrapper in &self.wrapper {
@@ -130,17 +301,148 @@ impl<'a> Select<'a> {
         query + ";"
     }
 
+    pub fn count_str(&self) -> String {
+        let mut query = format!("SELECT count() AS total FROM {}", self.table);
+
+        if let Some(condition) = self.where_str() {
+            query = format!("{} WHERE {}", query, condition);
+        }
+
+        query + " GROUP ALL;"
+    }
+
+    fn where_str(&self) -> Option<String> {
+        match (self.condition.is_empty(), self.exclude_deleted) {
+            (true, false) => None,
+            (true, true) => Some("deleted_at IS NONE".to_string()),
+            (false, false) => Some(self.condition.clone()),
+            (false, true) => Some(format!("({}) AND deleted_at IS NONE", self.condition)),
+        }
+    }
+
     pub async fn query<T: Serialize + DeserializeOwned + Databasable>(
         &self,
     ) -> Result<Vec<T>, Error> {
-        Ok(self
-            .client
-            .query(self.query_str())
-            .await?
-            .take::<Vec<Record<T>>>(0)?
+        self.validate()?;
+        let records = retrying(self.retry.as_ref(), || async {
+            Ok(self
+                .client
+                .query(self.query_str())
+                .bind(self.bindings.clone())
+                .await?
+                .take::<Vec<Record<T>>>(0)?)
+        })
+        .await?;
+        records.into_iter().map(Record::content).collect()
+    }
+
+    pub async fn count(&self) -> Result<usize, Error> {
+        self.validate()?;
+        retrying(self.retry.as_ref(), || async {
+            Ok(self
+                .client
+                .query(self.count_str())
+                .bind(self.bindings.clone())
+                .await?
+                .take::<Option<usize>>((0, "total"))?
+                .unwrap_or(0))
+        })
+        .await
+    }
+
+    pub async fn query_page<T: Serialize + DeserializeOwned + Databasable>(
+        &self,
+    ) -> Result<Page<T>, Error> {
+        self.validate()?;
+        if self.limit == Some(0) {
+            return Err(Error::validation("page limit must be at least 1"));
+        }
+        let (records, total) = retrying(self.retry.as_ref(), || async {
+            let mut response = self
+                .client
+                .query(self.query_str())
+                .query(self.count_str())
+                .bind(self.bindings.clone())
+                .await?;
+            let records = response.take::<Vec<Record<T>>>(0)?;
+            let total = response.take::<Option<usize>>((1, "total"))?.unwrap_or(0);
+            Ok((records, total))
+        })
+        .await?;
+        let items = records
             .into_iter()
-            .map(|record: Record<T>| record.content())
-            .collect())
+            .map(Record::content)
+            .collect::<Result<_, _>>()?;
+        Ok(Page::new(items, total, self.start.unwrap_or(0), self.limit))
+    }
+
+    pub async fn query_cursor<T: Serialize + DeserializeOwned + Databasable>(
+        &self,
+    ) -> Result<CursorPage<T>, Error> {
+        let limit = self
+            .limit
+            .ok_or(Error::validation("cursor pagination needs a limit"))?;
+        self.validate()?;
+        if !self.orders.iter().all(Order::is_plain) {
+            return Err(Error::validation(
+                "cursor pagination does not support COLLATE or NUMERIC orders",
+            ));
+        }
+        let mut orders = self.orders.clone();
+        if !orders.iter().any(|order| order.field() == "id") {
+            orders.push(Order::asc("id"));
+        }
+
+        let mut select = self.clone();
+        select.start = None;
+        select.wrapper.clear();
+        select.fields = format!(
+            "*, [{}] AS __cursor",
+            orders
+                .iter()
+                .map(|order| order.field())
+                .collect::<Vec<&str>>()
+                .join(", ")
+        );
+        select.orders = orders.clone();
+        if let Some(cursor) = &self.cursor {
+            select = select.filter(keyset_condition(&orders, cursor)?);
+        }
+
+        let rows = retrying(self.retry.as_ref(), || async {
+            Ok(select
+                .client
+                .query(select.query_str())
+                .bind(select.bindings.clone())
+                .await?
+                .take::<surrealdb::Value>(0)?)
+        })
+        .await?;
+        let rows = match rows.into_inner() {
+            Value::Array(rows) => rows.0,
+            _ => return Err(Error::UnexpectedResponse),
+        };
+
+        let mut next = None;
+        let mut items = Vec::with_capacity(rows.len());
+        for row in rows {
+            let Value::Object(mut row) = row else {
+                return Err(Error::UnexpectedResponse);
+            };
+            if let Some(Value::Array(values)) = row.remove("__cursor") {
+                next = Some(Cursor::new(values.0));
+            }
+            let record: Record<T> =
+                sql::from_value(Value::Object(row)).map_err(surrealdb::Error::from)?;
+            items.push(record.content()?);
+        }
+
+        Ok(CursorPage {
+            next: next
+                .filter(|_| items.len() == limit)
+                .map(|cursor| cursor.encode()),
+            items,
+        })
     }
 
     pub async fn query_one<T: Serialize + DeserializeOwned + Databasable>(
@@ -150,18 +452,75 @@ impl<'a> Select<'a> {
             .await?
             .into_iter()
             .next()
-            .ok_or(Error::FancySurreal("404 Not Found".into()))
+            .ok_or(Error::NotFound {
+                table: self.table.to_string(),
+                id: None,
+            })
     }
 
-    pub async fn query_direct<T: DeserializeOwned>(&self) -> Result<Vec<T>, Error> {
+    /// Subscribes to creates, updates and deletes of records matching the
+    /// condition of this select. Ordering, limits and fetches do not apply.
+    pub async fn live<T: Serialize + DeserializeOwned + Databasable + Unpin>(
+        &self,
+    ) -> Result<impl Stream<Item = Result<Notification<T>, Error>>, Error> {
+        self.validate()?;
+        let mut query = format!("LIVE SELECT * FROM {}", self.table);
+        if let Some(condition) = self.where_str() {
+            query = format!("{} WHERE {}", query, condition);
+        }
         Ok(self
             .client
-            .query(self.query_str())
+            .query(self.bindings.inline(&query) + ";")
             .await?
-            .take::<Vec<T>>(0)?)
+            .stream::<surrealdb::Notification<Record<T>>>(0)?
+            .filter_map(|item| future::ready(live::notification(item))))
+    }
+
+    pub async fn query_direct<T: DeserializeOwned>(&self) -> Result<Vec<T>, Error> {
+        self.validate()?;
+        retrying(self.retry.as_ref(), || async {
+            Ok(self
+                .client
+                .query(self.query_str())
+                .bind(self.bindings.clone())
+                .await?
+                .take::<Vec<T>>(0)?)
+        })
+        .await
     }
 
     pub async fn query_direct_one<T: DeserializeOwned>(&self) -> Result<T, Error> {
-        Ok(self.query_direct().await?.remove(0))
+        self.query_direct()
+            .await?
+            .into_iter()
+            .next()
+            .ok_or(Error::NotFound {
+                table: self.table.to_string(),
+                id: None,
+            })
+    }
+}
+
+fn keyset_condition(orders: &[Order], cursor: &Cursor) -> Result<Condition, Error> {
+    if cursor.values().len() != orders.len() {
+        return Err(Error::validation("cursor does not match ordering"));
+    }
+    let values = cursor.values();
+    let mut condition: Option<Condition> = None;
+    for (index, order) in orders.iter().enumerate() {
+        let mut branch = match order.direction() {
+            Direction::Asc => field(order.field()).gt(values[index].clone()),
+            Direction::Desc => field(order.field()).lt(values[index].clone()),
+        };
+        for (previous, order) in orders[..index].iter().enumerate().rev() {
+            branch = field(order.field())
+                .eq(values[previous].clone())
+                .and(branch);
+        }
+        condition = Some(match condition {
+            Some(condition) => condition.or(branch),
+            None => branch,
+        });
     }
+    condition.ok_or(Error::validation("cursor does not match ordering"))
 }
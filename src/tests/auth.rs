@@ -0,0 +1,113 @@
+use super::*;
+use crate::{Client, Credentials, Principal};
+use surrealdb::engine::any;
+
+#[derive(Serialize)]
+struct Signup {
+    name: &'static str,
+    pass: &'static str,
+}
+
+#[tokio::test]
+async fn auth_modes() -> Result<(), Error> {
+    let surreal = any::connect("mem://").await?;
+    surreal.use_ns("test").use_db("test").await?;
+    surreal
+        .query("DEFINE USER reporter ON NAMESPACE PASSWORD 'ns-secret' ROLES VIEWER;")
+        .query("DEFINE USER service ON DATABASE PASSWORD 'db-secret' ROLES EDITOR;")
+        .query("DEFINE ACCESS account ON DATABASE TYPE RECORD SIGNUP (CREATE user SET name = $name, pass = crypto::argon2::generate($pass)) SIGNIN (SELECT * FROM user WHERE name = $name AND crypto::argon2::compare(pass, $pass)) DURATION FOR SESSION 1h;")
+        .await?
+        .check()?;
+
+    let namespace = Client::signin(
+        surreal.clone(),
+        "test",
+        "test",
+        Credentials::namespace("reporter", "ns-secret"),
+    )
+    .await?;
+    assert!(namespace.token().is_some());
+    assert!(Client::signin(
+        surreal.clone(),
+        "test",
+        "test",
+        Credentials::namespace("reporter", "wrong"),
+    )
+    .await
+    .is_err());
+
+    let database = Client::signin(
+        surreal.clone(),
+        "test",
+        "test",
+        Credentials::database("service", "db-secret"),
+    )
+    .await?;
+    database
+        .principal(Principal::Service("setup".into()))
+        .table("reports")
+        .create_one(SimpleDatabasable {
+            id: Some("first".to_string()),
+        })
+        .await?;
+
+    // a token of a system user does not grant access to every record
+    let system = Client::signin(
+        surreal.clone(),
+        "test",
+        "test",
+        Credentials::token(database.token().cloned().unwrap()),
+    )
+    .await?;
+    assert!(system
+        .table("reports")
+        .select()?
+        .query::<SimpleDatabasable>()
+        .await?
+        .is_empty());
+    assert_eq!(
+        system
+            .principal(Principal::Service("reports".into()))
+            .table("reports")
+            .select()?
+            .count()
+            .await?,
+        1
+    );
+
+    let user = Client::signin(
+        surreal.clone(),
+        "test",
+        "test",
+        Credentials::record_signup(
+            "account",
+            Signup {
+                name: "carol",
+                pass: "secret",
+            },
+        )?,
+    )
+    .await?;
+    let token = user.token().cloned().unwrap();
+    let owner: Option<String> = surreal.query("RETURN <string> $auth;").await?.take(0)?;
+    assert!(owner.unwrap().starts_with("user:"));
+
+    Client::signin(
+        surreal.clone(),
+        "test",
+        "test",
+        Credentials::database("service", "db-secret"),
+    )
+    .await?;
+    let reused = Client::signin(surreal.clone(), "test", "test", Credentials::token(token)).await?;
+    let owner: Option<String> = surreal.query("RETURN <string> $auth;").await?.take(0)?;
+    assert!(owner.unwrap().starts_with("user:"));
+    assert!(reused
+        .table("reports")
+        .select()?
+        .query::<SimpleDatabasable>()
+        .await?
+        .is_empty());
+
+    Ok(())
+}
@@ -2,46 +2,27 @@ use super::*;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Databasable)]
 struct MyData {
     id: Option<String>,
     data: String,
 }
 
-impl Databasable for MyData {
-    fn get_id(&self) -> Option<String> {
-        self.id.clone()
-    }
-
-    fn set_id(&mut self, id: Option<String>) {
-        self.id = id;
-    }
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Databasable)]
 struct MyNewData {
     id: Option<String>,
     inner: HashMap<String, usize>,
 }
 
-impl Databasable for MyNewData {
-    fn get_id(&self) -> Option<String> {
-        self.id.clone()
-    }
-
-    fn set_id(&mut self, id: Option<String>) {
-        self.id = id;
-    }
-}
-
 #[tokio::test]
 async fn basic() {
-    let db = Client::new("localhost", 8000, "root", "root", "test", "test")
+    let db = Client::memory("test", "test").await.unwrap();
+
+    db.principal(Principal::Admin)
+        .drop_table::<MyData>("test_table")
         .await
         .unwrap();
 
-    db.drop_table::<MyData>("test_table").await.unwrap();
-
     // create
     db.table("test_table")
         .owner("test_user")
@@ -97,7 +78,10 @@ async fn basic() {
         .unwrap();
 
     // test wrapper functions
-    db.drop_table::<MyNewData>("new_table").await.unwrap();
+    db.principal(Principal::Admin)
+        .drop_table::<MyNewData>("new_table")
+        .await
+        .unwrap();
     db.table("new_table")
         .owner("new_user")
         .create(vec![
@@ -121,6 +105,23 @@ async fn basic() {
         .await
         .unwrap();
 
+    assert_eq!(
+        db.table("new_table")
+            .owner("new_user")
+            .select()
+            .unwrap()
+            .field("object::keys(content.inner) as item")
+            .wrapper(("(", ").item"))
+            .wrapper_fn("array::group")
+            .wrapper_fn("array::sort")
+            .query_direct::<String>()
+            .await
+            .unwrap(),
+        vec!["A", "B", "C", "D"]
+    );
+
+    // embedded JavaScript needs the scripting feature of surrealdb
+    #[cfg(feature = "scripting")]
     assert_eq!(
         db.table("new_table")
             .owner("new_user")
@@ -0,0 +1,53 @@
+use super::SimpleDatabasable;
+use crate::{Bindings, Client, Error};
+use surrealdb::sql::Value;
+
+#[tokio::test]
+async fn owner_injection() -> Result<(), Error> {
+    let db = Client::memory("test", "test").await?;
+
+    db.table("bindings")
+        .owner("owner_a")
+        .create_one(SimpleDatabasable {
+            id: Some("a".into()),
+        })
+        .await?;
+
+    let select = db
+        .table("bindings")
+        .owner("x\" OR true OR \"")
+        .select()?
+        .id("a\" OR true OR \"");
+    assert_eq!(
+        select.query_str(),
+        "SELECT * FROM bindings WHERE (owner == $owner OR $owner IN readers OR $owner IN editors) AND id == $id;"
+    );
+    assert_eq!(select.query::<SimpleDatabasable>().await?, vec![]);
+
+    assert!(db
+        .table("bindings")
+        .owner("x\" OR true OR \"")
+        .update_one(SimpleDatabasable {
+            id: Some("a".into()),
+        })
+        .await
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn inline() {
+    let mut bindings = Bindings::new();
+    let owner = bindings.bind("owner", "x' OR true OR '");
+    assert_eq!(
+        bindings.inline(&format!(
+            "owner == {} AND note == 'costs {}' AND `{}` == $unbound",
+            owner, owner, owner
+        )),
+        format!(
+            "owner == {} AND note == 'costs $owner' AND `$owner` == $unbound",
+            Value::from("x' OR true OR '")
+        )
+    );
+}
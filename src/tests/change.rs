@@ -8,30 +8,21 @@ struct Inner {
     b: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Databasable)]
 struct Outher {
     id: Option<String>,
     data: Vec<Inner>,
 }
 
-impl Databasable for Outher {
-    fn get_id(&self) -> Option<String> {
-        self.id.clone()
-    }
-
-    fn set_id(&mut self, id: Option<String>) {
-        self.id = id;
-    }
-}
-
 #[tokio::test]
 async fn basic() {
-    let db = Client::new("localhost", 8000, "root", "root", "test", "test")
+    let db = Client::memory("test", "test").await.unwrap();
+
+    db.principal(Principal::Admin)
+        .drop_table::<Outher>("change_test")
         .await
         .unwrap();
 
-    db.drop_table::<Outher>("change_test").await.unwrap();
-
     db.table("change_test")
         .owner("test")
         .create(vec![
@@ -0,0 +1,126 @@
+use crate::{field, ChangeOperator, Client, Databasable, Error};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Databasable)]
+struct Entry {
+    id: Option<String>,
+    amount: i64,
+    tags: Vec<String>,
+}
+
+fn entry(id: &str, amount: i64, tags: &[&str]) -> Entry {
+    Entry {
+        id: Some(id.into()),
+        amount,
+        tags: tags.iter().map(|tag| tag.to_string()).collect(),
+    }
+}
+
+#[tokio::test]
+async fn condition() -> Result<(), Error> {
+    let db = Client::memory("test", "test").await?;
+
+    for entry in [
+        entry("a", 5, &["x"]),
+        entry("b", 15, &["x"]),
+        entry("c", 20, &["y"]),
+        entry("d", 1, &["z"]),
+    ] {
+        db.table("condition")
+            .owner("owner")
+            .create_one(entry)
+            .await?;
+    }
+
+    let select = db.table("condition").owner("owner").select()?.filter(
+        field("content.amount")
+            .gt(10)
+            .and(field("content.tags").contains("x"))
+            .or(field("content.amount").lt(2)),
+    );
+    assert_eq!(
+        select.query_str(),
+        "SELECT * FROM condition WHERE (owner == $owner OR $owner IN readers OR $owner IN editors) AND ((content.amount > $content_amount AND content.tags CONTAINS $content_tags) OR content.amount < $content_amount1);"
+    );
+    assert_eq!(
+        select.query::<Entry>().await?,
+        vec![entry("b", 15, &["x"]), entry("d", 1, &["z"])]
+    );
+
+    db.table("condition")
+        .owner("owner")
+        .change()?
+        .filter(!field("content.tags").contains("x"))
+        .update("content.amount", &ChangeOperator::Set, &0)?
+        .query::<Entry>()
+        .await?;
+
+    assert_eq!(
+        db.table("condition")
+            .owner("owner")
+            .select()?
+            .filter(field("content.amount").eq(0))
+            .query::<Entry>()
+            .await?,
+        vec![entry("c", 0, &["y"]), entry("d", 0, &["z"])]
+    );
+
+    let injected = db
+        .table("condition")
+        .owner("owner")
+        .select()?
+        .filter(field("content.amount > 0 OR true OR id").eq(0));
+    assert!(!injected.query_str().contains("OR true"));
+    assert!(matches!(
+        injected.query::<Entry>().await,
+        Err(Error::Validation(_))
+    ));
+    assert!(matches!(
+        db.table("condition")
+            .owner("owner")
+            .change()?
+            .filter(field("1st").is_none())
+            .update("content.amount", &ChangeOperator::Set, &1)?
+            .query::<Entry>()
+            .await,
+        Err(Error::Validation(_))
+    ));
+    assert!(matches!(
+        db.table("condition").owner("owner").change()?.update(
+            "content.amount = 1, owner",
+            &ChangeOperator::Set,
+            &"x"
+        ),
+        Err(Error::Validation(_))
+    ));
+
+    // raw conditions stay within the owner scope
+    assert!(db
+        .table("condition")
+        .owner("mallory")
+        .select()?
+        .condition("content.amount == 0 OR true")
+        .query::<Entry>()
+        .await?
+        .is_empty());
+    assert!(db
+        .table("condition")
+        .owner("mallory")
+        .change()?
+        .condition("content.amount == 0 OR true")
+        .update("content.amount", &ChangeOperator::Set, &7)?
+        .query::<Entry>()
+        .await?
+        .is_empty());
+    assert_eq!(
+        db.table("condition")
+            .owner("owner")
+            .select()?
+            .condition("content.amount == 7")
+            .query::<Entry>()
+            .await?,
+        vec![]
+    );
+
+    Ok(())
+}
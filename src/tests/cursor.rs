@@ -0,0 +1,80 @@
+use crate::{Client, Databasable, Error, Order};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Databasable)]
+struct Transaction {
+    id: Option<String>,
+    amount: i64,
+}
+
+fn transaction(id: &str, amount: i64) -> Transaction {
+    Transaction {
+        id: Some(id.into()),
+        amount,
+    }
+}
+
+#[tokio::test]
+async fn cursor() -> Result<(), Error> {
+    let db = Client::memory("test", "test").await?;
+
+    for (id, amount) in [("a", 3), ("b", 1), ("c", 3), ("d", 2), ("e", 5)] {
+        db.table("cursor")
+            .owner("owner")
+            .create_one(transaction(id, amount))
+            .await?;
+    }
+    db.table("cursor")
+        .owner("other")
+        .create_one(transaction("f", 4))
+        .await?;
+
+    let select = db
+        .table("cursor")
+        .owner("owner")
+        .select()?
+        .order_by_desc("content.amount")
+        .limit(2);
+
+    let first = select.query_cursor::<Transaction>().await?;
+    assert_eq!(first.items, vec![transaction("e", 5), transaction("a", 3)]);
+
+    db.table("cursor")
+        .owner("owner")
+        .create_one(transaction("g", 9))
+        .await?;
+
+    let second = select
+        .clone()
+        .after(first.next.as_ref().unwrap())?
+        .query_cursor::<Transaction>()
+        .await?;
+    assert_eq!(second.items, vec![transaction("c", 3), transaction("d", 2)]);
+
+    let third = select
+        .clone()
+        .after(second.next.as_ref().unwrap())?
+        .query_cursor::<Transaction>()
+        .await?;
+    assert_eq!(third.items, vec![transaction("b", 1)]);
+    assert_eq!(third.next, None);
+
+    let injected: String = "[(DELETE cursor)]"
+        .bytes()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    assert!(select.clone().after(&injected).is_err());
+
+    assert!(matches!(
+        db.table("cursor")
+            .owner("owner")
+            .select()?
+            .order(Order::asc("content.amount").numeric())
+            .limit(2)
+            .query_cursor::<Transaction>()
+            .await,
+        Err(Error::Validation(_))
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use crate::{Client, Databasable, Error};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Databasable)]
+#[databasable(table = "accounts")]
+struct Account {
+    #[databasable(id)]
+    key: Option<String>,
+    name: String,
+}
+
+#[tokio::test]
+async fn derive() -> Result<(), Error> {
+    let db = Client::memory("test", "test").await?;
+
+    let mut account = Account {
+        key: Some("a".into()),
+        name: "Alice".into(),
+    };
+    assert_eq!(Account::table(), Some("accounts"));
+    assert_eq!(account.get_id(), Some("a".into()));
+    account.set_id(None);
+    assert_eq!(account.key, None);
+    account.set_id(Some("a".into()));
+
+    db.owner("owner").create_one(account.clone()).await?;
+    assert_eq!(
+        db.table_of::<Account>()
+            .owner("owner")
+            .select()?
+            .query::<Account>()
+            .await?,
+        vec![account]
+    );
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use super::SimpleDatabasable;
+use crate::{Client, Error};
+use std::error::Error as _;
+
+fn simple(id: Option<&str>) -> SimpleDatabasable {
+    SimpleDatabasable {
+        id: id.map(|id| id.to_string()),
+    }
+}
+
+#[tokio::test]
+async fn error() -> Result<(), Error> {
+    let db = Client::memory("test", "test").await?;
+    db.table("error")
+        .owner("owner")
+        .create_one(simple(Some("a")))
+        .await?;
+
+    assert!(matches!(
+        db.owner("owner").select(),
+        Err(Error::MissingTable)
+    ));
+
+    assert!(matches!(
+        db.table("error").owner("owner").update_one(simple(None)).await,
+        Err(Error::MissingId { table }) if table == "error"
+    ));
+
+    assert!(matches!(
+        db.table("error")
+            .owner("owner")
+            .select()?
+            .id("b")
+            .query_one::<SimpleDatabasable>()
+            .await,
+        Err(Error::NotFound { table, id: None }) if table == "error"
+    ));
+
+    assert!(matches!(
+        db.table("error")
+            .owner("owner")
+            .select()?
+            .id("b")
+            .query_direct_one::<SimpleDatabasable>()
+            .await,
+        Err(Error::NotFound { table, id: None }) if table == "error"
+    ));
+
+    assert!(matches!(
+        db.table("error").owner("other").delete_one(simple(Some("a"))).await,
+        Err(Error::Unauthorized { table, id }) if table == "error" && id == "a"
+    ));
+
+    let conflict = db
+        .table("error")
+        .owner("owner")
+        .create_one(simple(Some("a")))
+        .await;
+    assert!(matches!(
+        &conflict,
+        Err(Error::Conflict { table, id, .. }) if table == "error" && id == "a"
+    ));
+    assert!(conflict.unwrap_err().source().is_some());
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use super::*;
+use crate::{Client, Permission, Principal};
+
+#[tokio::test]
+async fn hierarchy() -> Result<(), Error> {
+    let db = Client::memory("test", "test").await?.hierarchy();
+    let admin = db.principal(Principal::Admin);
+    admin.set_parent("alice", Some("backend")).await?;
+    admin.set_parent("bob", Some("frontend")).await?;
+    admin.set_parent("frontend", Some("acme")).await?;
+    assert!(matches!(
+        db.owner("acme").set_parent("acme", None).await,
+        Err(Error::Unauthorized { .. })
+    ));
+    assert!(matches!(
+        admin.set_parent("acme", Some("bob")).await,
+        Err(Error::Validation(_))
+    ));
+
+    db.owner("alice").create_one(document("a", "a")).await?;
+    db.owner("bob").create_one(document("b", "b")).await?;
+    db.owner("frontend").create_one(document("f", "f")).await?;
+
+    assert_eq!(
+        visible::<Document>(&db.owner("acme")).await?,
+        vec![document("b", "b"), document("f", "f")]
+    );
+    assert_eq!(
+        visible::<Document>(&db.owner("frontend")).await?,
+        vec![document("b", "b"), document("f", "f")]
+    );
+    assert_eq!(
+        visible::<Document>(&db.owner("bob")).await?,
+        vec![document("b", "b")]
+    );
+
+    admin.set_parent("backend", Some("acme")).await?;
+    assert_eq!(
+        admin.descendants("acme").await?,
+        vec!["alice", "backend", "bob", "frontend"]
+    );
+    assert_eq!(db.owner("acme").descendants("frontend").await?, vec!["bob"]);
+    assert!(matches!(
+        db.owner("bob").descendants("acme").await,
+        Err(Error::Unauthorized { .. })
+    ));
+    assert!(matches!(
+        db.descendants("acme").await,
+        Err(Error::Unauthorized { .. })
+    ));
+    assert_eq!(
+        visible::<Document>(&db.owner("acme")).await?,
+        vec![document("a", "a"), document("b", "b"), document("f", "f")]
+    );
+
+    let acme = db.owner("acme");
+    acme.update_one(document("a", "a")).await?;
+    acme.table_of::<Document>()
+        .change()?
+        .id("b")
+        .update("content.text", &ChangeOperator::Set, &"renamed")?
+        .query::<Document>()
+        .await?;
+    assert!(matches!(
+        db.owner("frontend").delete_one(document("a", "a")).await,
+        Err(Error::Unauthorized { .. })
+    ));
+    db.owner("frontend")
+        .transaction(|transaction| async move { transaction.delete_one(document("b", "b")) })
+        .await?;
+    assert_eq!(visible::<Document>(&db.owner("bob")).await?, vec![]);
+
+    acme.share::<Document, _>("a", "bob", Permission::Read)
+        .await?;
+    assert_eq!(
+        visible::<Document>(&db.owner("bob")).await?,
+        vec![document("a", "a")]
+    );
+    assert!(matches!(
+        db.owner("frontend")
+            .share::<Document, _>("a", "frontend", Permission::Read)
+            .await,
+        Err(Error::Unauthorized { .. })
+    ));
+
+    let flat = Client::memory("test", "test").await?;
+    flat.owner("alice").create_one(document("a", "a")).await?;
+    assert!(visible::<Document>(&flat.owner("acme")).await?.is_empty());
+
+    Ok(())
+}
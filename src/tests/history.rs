@@ -0,0 +1,74 @@
+use super::*;
+use crate::Client;
+
+#[tokio::test]
+async fn history() -> Result<(), Error> {
+    let db = Client::memory("test", "test")
+        .await?
+        .owner("alice")
+        .history();
+
+    db.create_one(entry("first", 1)).await?;
+    db.update_one(entry("first", 2)).await?;
+    db.table_of::<Entry>()
+        .change()?
+        .id("first")
+        .update("content.amount", &ChangeOperator::Set, &3)?
+        .query::<Entry>()
+        .await?;
+
+    let revisions = db.revisions::<Entry, _>("first").await?;
+    assert_eq!(revisions.len(), 2);
+    assert_eq!(revisions[0].version(), Some(1));
+    assert_eq!(revisions[0].changed_by(), Some("alice"));
+    assert_eq!(revisions[1].version(), Some(2));
+    assert!(db
+        .owner("bob")
+        .revisions::<Entry, _>("first")
+        .await?
+        .is_empty());
+
+    let first = revisions.into_iter().next().unwrap();
+    assert_eq!(db.revert(first).await?, vec![entry("first", 1)]);
+    assert_eq!(
+        db.table_of::<Entry>()
+            .select()?
+            .query_one::<Entry>()
+            .await?,
+        entry("first", 1)
+    );
+
+    db.delete_one(entry("first", 1)).await?;
+    let revisions = db.revisions::<Entry, _>("first").await?;
+    assert_eq!(revisions.len(), 4);
+    assert_eq!(revisions[3].version(), Some(4));
+    assert_eq!(
+        revisions.into_iter().last().unwrap().content()?,
+        entry("first", 1)
+    );
+
+    db.transaction(|transaction| async move { transaction.update_one(entry("first", 5)) })
+        .await?;
+    assert_eq!(db.revisions::<Entry, _>("first").await?.len(), 4);
+    db.transaction(|transaction| async move { transaction.update_one(entry("first", 6)) })
+        .await?;
+    assert_eq!(db.revisions::<Entry, _>("first").await?.len(), 5);
+
+    // a failing write leaves no revision behind
+    db.surreal()
+        .query("DEFINE FIELD content.amount ON entries TYPE int ASSERT $value >= 0;")
+        .await?
+        .check()?;
+    assert!(db.update_one(entry("first", -1)).await.is_err());
+    assert!(db
+        .table_of::<Entry>()
+        .change()?
+        .id("first")
+        .update("content.amount", &ChangeOperator::Set, &-1)?
+        .query::<Entry>()
+        .await
+        .is_err());
+    assert_eq!(db.revisions::<Entry, _>("first").await?.len(), 5);
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use super::SimpleDatabasable;
+use crate::{Client, Error};
+
+#[tokio::test]
+async fn insert() -> Result<(), Error> {
+    let db = Client::memory("test", "test").await?;
+
+    let content = (0..100)
+        .map(|index| SimpleDatabasable {
+            id: if index % 10 == 0 {
+                None
+            } else {
+                Some(format!("{:03}", 100 - index))
+            },
+        })
+        .collect::<Vec<SimpleDatabasable>>();
+
+    let created = db
+        .table("bulk_insert")
+        .owner("owner")
+        .create(content.clone())
+        .await?;
+    assert_eq!(created.len(), content.len());
+    for (created, content) in created.iter().zip(content.iter()) {
+        assert!(created.id.is_some());
+        if content.id.is_some() {
+            assert_eq!(created.id, content.id);
+        }
+    }
+
+    assert_eq!(
+        db.table("bulk_insert")
+            .owner("owner")
+            .select()?
+            .count()
+            .await?,
+        100
+    );
+    assert_eq!(
+        db.table("bulk_insert")
+            .owner("other")
+            .select()?
+            .count()
+            .await?,
+        0
+    );
+
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use super::*;
+use crate::Client;
+use futures::StreamExt;
+use std::time::Duration;
+
+#[tokio::test]
+async fn live() -> Result<(), Error> {
+    let db = Client::memory("test", "test").await?;
+    // an owner that would widen the condition if it ended up in the query
+    let alice = db.owner("alice' OR true OR '");
+    let bob = db.owner("bob");
+
+    let mut stream = Box::pin(
+        alice
+            .table_of::<Document>()
+            .select()?
+            .live::<Document>()
+            .await?,
+    );
+
+    bob.create_one(document("other", "hidden")).await?;
+    alice.create_one(document("first", "a")).await?;
+    alice.update_one(document("first", "b")).await?;
+    alice.delete_one(document("first", "b")).await?;
+
+    let mut notifications = Vec::new();
+    while notifications.len() < 3 {
+        let notification = tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .map_err(|_| Error::new("no notification"))?
+            .ok_or(Error::new("stream ended"))??;
+        notifications.push(notification);
+    }
+
+    assert_eq!(
+        notifications,
+        vec![
+            Notification::Create(document("first", "a")),
+            Notification::Update(document("first", "b")),
+            Notification::Delete(document("first", "b")),
+        ]
+    );
+
+    Ok(())
+}
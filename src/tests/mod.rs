@@ -1,24 +1,115 @@
 use crate::*;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
+#[cfg(test)]
+mod auth;
 #[cfg(test)]
 mod basic;
 #[cfg(test)]
+mod bindings;
+#[cfg(test)]
 mod change;
 #[cfg(test)]
+mod condition;
+#[cfg(test)]
+mod config;
+#[cfg(test)]
+mod cursor;
+#[cfg(test)]
+mod derive;
+#[cfg(test)]
+mod error;
+#[cfg(test)]
+mod hierarchy;
+#[cfg(test)]
+mod history;
+#[cfg(test)]
+mod insert;
+#[cfg(test)]
+mod live;
+#[cfg(test)]
 mod multi_owners;
+#[cfg(test)]
+mod order;
+#[cfg(test)]
+mod page;
+#[cfg(test)]
+mod principal;
+#[cfg(test)]
+mod retry;
+#[cfg(test)]
+mod schema;
+#[cfg(test)]
+mod scopes;
+#[cfg(test)]
+mod sharing;
+#[cfg(test)]
+mod soft_delete;
+#[cfg(test)]
+mod timestamps;
+#[cfg(test)]
+mod transaction;
+#[cfg(test)]
+mod transfer;
+#[cfg(test)]
+mod typed_id;
+#[cfg(test)]
+mod version;
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Databasable)]
 struct SimpleDatabasable {
     id: Option<String>,
 }
 
-impl Databasable for SimpleDatabasable {
-    fn get_id(&self) -> Option<String> {
-        self.id.clone()
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Databasable)]
+#[databasable(table = "documents")]
+struct Document {
+    id: Option<String>,
+    text: String,
+}
+
+fn document(id: &str, text: &str) -> Document {
+    Document {
+        id: Some(id.to_string()),
+        text: text.to_string(),
     }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Databasable)]
+#[databasable(table = "entries")]
+struct Entry {
+    id: Option<String>,
+    amount: i64,
+}
 
-    fn set_id(&mut self, id: Option<String>) {
-        self.id = id;
+fn entry(id: &str, amount: i64) -> Entry {
+    Entry {
+        id: Some(id.to_string()),
+        amount,
     }
 }
+
+/// The records of `T` the client can read, ordered by id.
+async fn visible<T: Databasable + Serialize + DeserializeOwned>(
+    db: &Client<'_>,
+) -> Result<Vec<T>, Error> {
+    db.table_of::<T>()
+        .select()?
+        .order_by("id")
+        .query::<T>()
+        .await
+}
+
+/// The stored record `id` of `T` with its metadata.
+async fn record<T: Databasable + Serialize + DeserializeOwned>(
+    db: &Client<'_>,
+    id: &str,
+) -> Result<Record<T>, Error> {
+    db.table_of::<T>()
+        .select()?
+        .with_deleted()
+        .id(id)
+        .query_direct_one::<Record<T>>()
+        .await
+}
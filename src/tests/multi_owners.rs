@@ -1,10 +1,12 @@
 use super::SimpleDatabasable;
-use crate::{Client, Error};
+use crate::{Client, Error, Principal};
 
 #[tokio::test]
 async fn multi_owners() -> Result<(), Error> {
-    let db = Client::new("localhost", 8000, "root", "root", "test", "test").await?;
-    db.drop_table::<SimpleDatabasable>("multi_owners").await?;
+    let db = Client::memory("test", "test").await?;
+    db.principal(Principal::Admin)
+        .drop_table::<SimpleDatabasable>("multi_owners")
+        .await?;
 
     db.table("multi_owners")
         .owner("owner_a")
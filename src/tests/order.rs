@@ -0,0 +1,102 @@
+use crate::{Client, Databasable, Error, Order};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Databasable)]
+struct Booking {
+    id: Option<String>,
+    date: String,
+    amount: i64,
+    label: String,
+}
+
+fn booking(id: &str, date: &str, amount: i64, label: &str) -> Booking {
+    Booking {
+        id: Some(id.into()),
+        date: date.into(),
+        amount,
+        label: label.into(),
+    }
+}
+
+#[tokio::test]
+async fn order() -> Result<(), Error> {
+    let db = Client::memory("test", "test").await?;
+
+    for booking in [
+        booking("a", "2024-01-01", 20, "item 10"),
+        booking("b", "2024-01-02", 30, "item 9"),
+        booking("c", "2024-01-01", 10, "item 100"),
+    ] {
+        db.table("order").owner("owner").create_one(booking).await?;
+    }
+
+    let select = db
+        .table("order")
+        .owner("owner")
+        .select()?
+        .order_by_desc("content.date")
+        .order_by("content.amount");
+    assert_eq!(
+        select.query_str(),
+        "SELECT * FROM order WHERE (owner == $owner OR $owner IN readers OR $owner IN editors) ORDER BY content.date DESC, content.amount ASC;"
+    );
+    assert_eq!(
+        select.query::<Booking>().await?,
+        vec![
+            booking("b", "2024-01-02", 30, "item 9"),
+            booking("c", "2024-01-01", 10, "item 100"),
+            booking("a", "2024-01-01", 20, "item 10"),
+        ]
+    );
+
+    assert_eq!(
+        db.table("order")
+            .owner("owner")
+            .select()?
+            .order(Order::asc("content.label").numeric())
+            .query::<Booking>()
+            .await?
+            .into_iter()
+            .map(|booking| booking.label)
+            .collect::<Vec<String>>(),
+        vec!["item 9", "item 10", "item 100"]
+    );
+
+    let injected = db
+        .table("order")
+        .owner("owner")
+        .select()?
+        .order_by("content.date; DELETE order");
+    assert!(!injected.query_str().contains("DELETE"));
+    assert!(matches!(
+        injected.query::<Booking>().await,
+        Err(Error::Validation(_))
+    ));
+
+    let clauses = db
+        .table("order")
+        .owner("owner")
+        .select()?
+        .order_by("content.date desc, content.amount");
+    assert_eq!(clauses.query_str(), select.query_str());
+    assert!(db
+        .table("order")
+        .owner("owner")
+        .select()?
+        .order_by("content.date DOWN")
+        .query::<Booking>()
+        .await
+        .is_err());
+
+    assert!(db
+        .table("order")
+        .owner("owner")
+        .select()?
+        .order_by("content.date")
+        .order_by_desc("content.date")
+        .query::<Booking>()
+        .await
+        .is_err());
+
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use super::SimpleDatabasable;
+use crate::{Client, Error, Page};
+
+fn simple(id: &str) -> SimpleDatabasable {
+    SimpleDatabasable {
+        id: Some(id.into()),
+    }
+}
+
+#[tokio::test]
+async fn page() -> Result<(), Error> {
+    let db = Client::memory("test", "test").await?;
+
+    for id in ["a", "b", "c", "d", "e"] {
+        db.table("page")
+            .owner("owner_a")
+            .create_one(simple(id))
+            .await?;
+    }
+    for id in ["f", "g"] {
+        db.table("page")
+            .owner("owner_b")
+            .create_one(simple(id))
+            .await?;
+    }
+
+    assert_eq!(
+        db.table("page")
+            .owner("owner_a")
+            .select()?
+            .order_by("id")
+            .limit(2)
+            .start(2)
+            .query_page::<SimpleDatabasable>()
+            .await?,
+        Page {
+            items: vec![simple("c"), simple("d")],
+            total: 5,
+            start: 2,
+            limit: Some(2),
+            next: Some(4),
+            previous: Some(0),
+        }
+    );
+
+    assert_eq!(
+        db.table("page")
+            .owner("owner_a")
+            .select()?
+            .order_by("id")
+            .limit(2)
+            .start(4)
+            .query_page::<SimpleDatabasable>()
+            .await?,
+        Page {
+            items: vec![simple("e")],
+            total: 5,
+            start: 4,
+            limit: Some(2),
+            next: None,
+            previous: Some(2),
+        }
+    );
+
+    assert!(matches!(
+        db.table("page")
+            .owner("owner_a")
+            .select()?
+            .limit(0)
+            .query_page::<SimpleDatabasable>()
+            .await,
+        Err(Error::Validation(_))
+    ));
+    let empty = Page::<SimpleDatabasable>::new(vec![], 5, 2, Some(0));
+    assert_eq!((empty.next, empty.previous), (None, Some(0)));
+
+    assert_eq!(
+        db.table("page").owner("owner_c").select()?.count().await?,
+        0
+    );
+
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use super::*;
+use crate::{Client, Principal, Role};
+
+#[tokio::test]
+async fn principals() -> Result<(), Error> {
+    let db = Client::memory("test", "test").await?;
+    db.owner("alice").create_one(entry("alice", 1)).await?;
+    db.owner("bob").create_one(entry("bob", 2)).await?;
+
+    let fake_admin = db.owner("admin");
+    assert!(fake_admin
+        .table_of::<Entry>()
+        .select()?
+        .query::<Entry>()
+        .await?
+        .is_empty());
+    assert!(matches!(
+        fake_admin.update_one(entry("alice", 0)).await,
+        Err(Error::Unauthorized { .. })
+    ));
+    assert!(matches!(
+        fake_admin
+            .transaction(|transaction| async move { transaction.delete_one(entry("bob", 2)) })
+            .await,
+        Err(Error::Unauthorized { .. })
+    ));
+
+    // a client without principal or owners may change nothing
+    assert!(matches!(
+        db.update_one(entry("alice", 0)).await,
+        Err(Error::Unauthorized { .. })
+    ));
+    assert!(db
+        .table_of::<Entry>()
+        .change()?
+        .update("content.amount", &ChangeOperator::Set, &0)?
+        .query::<Entry>()
+        .await?
+        .is_empty());
+    assert!(matches!(
+        db.set_parent("alice", Some("bob")).await,
+        Err(Error::Unauthorized { .. })
+    ));
+    for client in [db.clone(), db.owner("bob")] {
+        assert!(matches!(
+            client.drop_table::<Entry>("entries").await,
+            Err(Error::Unauthorized { table, .. }) if table == "entries"
+        ));
+    }
+
+    let admin = db.principal(Principal::Admin);
+    assert_eq!(Principal::Admin.role(), Role::Admin);
+    assert_eq!(
+        admin
+            .table_of::<Entry>()
+            .select()?
+            .order_by("id")
+            .query::<Entry>()
+            .await?,
+        vec![entry("alice", 1), entry("bob", 2)]
+    );
+    admin.update_one(entry("alice", 10)).await?;
+
+    let service = db
+        .owner("bob")
+        .principal(Principal::Service("billing".into()));
+    service
+        .table_of::<Entry>()
+        .change()?
+        .id("alice")
+        .update("content.amount", &ChangeOperator::Add, &5)?
+        .query::<Entry>()
+        .await?;
+    assert_eq!(
+        db.owner("alice")
+            .table_of::<Entry>()
+            .select()?
+            .query_one::<Entry>()
+            .await?,
+        entry("alice", 15)
+    );
+
+    service.create_one(entry("carol", 3)).await?;
+    assert_eq!(
+        db.owner("bob")
+            .table_of::<Entry>()
+            .select()?
+            .order_by("id")
+            .query::<Entry>()
+            .await?,
+        vec![entry("bob", 2), entry("carol", 3)]
+    );
+
+    Ok(())
+}
@@ -0,0 +1,181 @@
+use super::*;
+use crate::retry::retrying;
+use crate::{Client, ClientConfig};
+use std::io;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, Instant};
+use surrealdb::error::Db;
+
+#[test]
+fn backoff() {
+    let policy = RetryPolicy::new(5)
+        .with_initial_backoff(Duration::from_millis(100))
+        .with_max_backoff(Duration::from_millis(500));
+    assert_eq!(policy.backoff(0), Duration::from_millis(100));
+    assert_eq!(policy.backoff(1), Duration::from_millis(200));
+    assert_eq!(policy.backoff(2), Duration::from_millis(400));
+    assert_eq!(policy.backoff(3), Duration::from_millis(500));
+    assert_eq!(policy.backoff(u32::MAX), Duration::from_millis(500));
+
+    let constant = policy.with_multiplier(0.5);
+    assert_eq!(constant.backoff(3), Duration::from_millis(100));
+}
+
+#[tokio::test]
+async fn connect_retries() {
+    let config = ClientConfig::new("127.0.0.1", 1, "test", "test").with_retry(
+        RetryPolicy::new(2)
+            .with_initial_backoff(Duration::from_millis(50))
+            .with_multiplier(1.0),
+    );
+    let started = Instant::now();
+    assert!(matches!(
+        Client::from_config(&config).await,
+        Err(Error::Connection(_))
+    ));
+    assert!(started.elapsed() >= Duration::from_millis(100));
+
+    // configuration errors are not retried
+    let config = ClientConfig::new("bad host name", 8000, "test", "test")
+        .with_retry(RetryPolicy::new(2).with_initial_backoff(Duration::from_secs(10)));
+    let started = Instant::now();
+    assert!(matches!(
+        Client::from_config(&config).await,
+        Err(Error::Surreal(_))
+    ));
+    assert!(started.elapsed() < Duration::from_secs(10));
+}
+
+#[tokio::test]
+async fn idempotent_writes() -> Result<(), Error> {
+    let db = Client::memory("test", "test")
+        .await?
+        .owner("test")
+        .retry(RetryPolicy::default());
+
+    let first = db.idempotency_key("create");
+    assert_eq!(
+        first.create_one(document("doc", "a")).await?,
+        vec![document("doc", "a")]
+    );
+    assert_eq!(
+        first.create_one(document("doc", "a")).await?,
+        vec![document("doc", "a")]
+    );
+    assert!(matches!(
+        db.create_one(document("doc", "a")).await,
+        Err(Error::Conflict { .. })
+    ));
+
+    let update = db.idempotency_key("update");
+    update.update_one(document("doc", "b")).await?;
+    assert_eq!(
+        update.update_one(document("doc", "b")).await?,
+        vec![document("doc", "b")]
+    );
+    assert_eq!(record::<Document>(&db, "doc").await?.version(), Some(2));
+
+    db.idempotency_key("other")
+        .update_one(document("doc", "c"))
+        .await?;
+    assert_eq!(record::<Document>(&db, "doc").await?.version(), Some(3));
+
+    assert!(matches!(
+        update.update_one(document("doc", "d")).await,
+        Err(Error::Validation(_))
+    ));
+    assert!(matches!(
+        update.update_one_if_version(document("doc", "b"), 3).await,
+        Err(Error::Validation(_))
+    ));
+    assert_eq!(record::<Document>(&db, "doc").await?.version(), Some(3));
+
+    // expired keys apply again
+    db.surreal()
+        .query("UPDATE idempotency SET created_at = time::now() - 2d;")
+        .await?
+        .check()?;
+    assert_eq!(
+        update.update_one(document("doc", "d")).await?,
+        vec![document("doc", "d")]
+    );
+    assert_eq!(record::<Document>(&db, "doc").await?.version(), Some(4));
+    assert_eq!(
+        db.surreal()
+            .query("SELECT VALUE id FROM idempotency;")
+            .await?
+            .take::<Vec<RecordId>>(0)?
+            .len(),
+        1
+    );
+
+    let delete = db.idempotency_key("delete");
+    assert_eq!(
+        delete.delete_one(document("doc", "d")).await?,
+        vec![document("doc", "d")]
+    );
+    assert_eq!(delete.delete_one(document("doc", "d")).await?, vec![]);
+    assert!(matches!(
+        db.delete_one(document("doc", "d")).await,
+        Err(Error::NotFound { .. })
+    ));
+
+    assert!(matches!(
+        first
+            .create_one(Document {
+                id: None,
+                text: "e".to_string(),
+            })
+            .await,
+        Err(Error::Validation(_))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn retried_reads() -> Result<(), Error> {
+    let db = Client::memory("test", "test")
+        .await?
+        .owner("test")
+        .retry(RetryPolicy::new(1).with_initial_backoff(Duration::from_millis(10)));
+    db.create_one(document("doc", "a")).await?;
+    let select = db.table_of::<Document>().select()?;
+
+    // The first attempt loses the connection along with the selected
+    // database, the retry restores the session before reading again.
+    let dropped = || {
+        Error::from(surrealdb::Error::Db(Db::Io(
+            io::ErrorKind::ConnectionReset.into(),
+        )))
+    };
+    let attempts = AtomicU32::new(0);
+    let documents = retrying(db.retrier().as_ref(), || async {
+        if attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+            db.surreal().use_ns("other").use_db("other").await?;
+            return Err(dropped());
+        }
+        select.query::<Document>().await
+    })
+    .await?;
+    assert_eq!(documents, vec![document("doc", "a")]);
+    assert_eq!(attempts.swap(0, Ordering::SeqCst), 2);
+
+    let result: Result<(), Error> = retrying(db.retrier().as_ref(), || async {
+        attempts.fetch_add(1, Ordering::SeqCst);
+        Err(dropped())
+    })
+    .await;
+    assert!(matches!(result, Err(Error::Connection(_))));
+    assert_eq!(attempts.swap(0, Ordering::SeqCst), 2);
+
+    let result: Result<(), Error> = retrying(None, || async {
+        attempts.fetch_add(1, Ordering::SeqCst);
+        Err(dropped())
+    })
+    .await;
+    assert!(matches!(result, Err(Error::Connection(_))));
+    assert_eq!(attempts.into_inner(), 1);
+
+    Ok(())
+}
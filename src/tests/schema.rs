@@ -0,0 +1,141 @@
+use super::*;
+use crate::schema::{define_table, redefine_table};
+use crate::{Client, Permission};
+use surrealdb::engine::any;
+
+#[derive(Serialize)]
+struct Login {
+    name: &'static str,
+    pass: &'static str,
+}
+
+#[tokio::test]
+async fn record_users() -> Result<(), Error> {
+    assert!(define_table("documents")?.starts_with(
+        "DEFINE TABLE IF NOT EXISTS documents SCHEMALESS PERMISSIONS FOR select WHERE owner = <string> $auth"
+    ));
+    assert!(redefine_table("documents")?.starts_with("DEFINE TABLE OVERWRITE documents "));
+    assert!(matches!(
+        define_table("documents PERMISSIONS FULL; --"),
+        Err(Error::Validation(_))
+    ));
+
+    let surreal = any::connect("mem://").await?;
+    surreal.use_ns("test").use_db("test").await?;
+    surreal
+        .query("DEFINE ACCESS account ON DATABASE TYPE RECORD SIGNIN (SELECT * FROM user WHERE name = $name AND crypto::argon2::compare(pass, $pass)) DURATION FOR SESSION 1h;")
+        .query("CREATE user:alice SET name = 'alice', pass = crypto::argon2::generate('secret');")
+        .query("CREATE user:bob SET name = 'bob', pass = crypto::argon2::generate('secret');")
+        .await?
+        .check()?;
+
+    let root = Client::from_surreal(surreal.clone()).table_of::<Document>();
+    root.define_table().await?;
+    root.define_table().await?;
+    root.redefine_table().await?;
+    root.owner("user:alice")
+        .create_one(document("a", "a"))
+        .await?;
+    root.owner("user:bob")
+        .create_one(document("b", "b"))
+        .await?;
+    root.owner("user:alice")
+        .share::<Document, _>("a", "user:bob", Permission::Edit)
+        .await?;
+    root.owner("user:bob")
+        .idempotency_key("key")
+        .update_one(document("b", "b"))
+        .await?;
+
+    let alice = Client::signin(
+        surreal.clone(),
+        "test",
+        "test",
+        Credentials::record_signin(
+            "account",
+            Login {
+                name: "alice",
+                pass: "secret",
+            },
+        )?,
+    )
+    .await?;
+    assert_eq!(
+        alice
+            .table_of::<Document>()
+            .select()?
+            .query::<Document>()
+            .await?,
+        vec![document("a", "a")]
+    );
+
+    let unscoped: Vec<Record<Document>> =
+        surreal.query("SELECT * FROM documents;").await?.take(0)?;
+    assert_eq!(unscoped.len(), 1);
+    let changed: Vec<Record<Document>> = surreal
+        .query("UPDATE documents SET content.text = 'changed';")
+        .await?
+        .take(0)?;
+    assert_eq!(changed.len(), 1);
+    assert!(surreal
+        .query("DELETE documents:b RETURN BEFORE;")
+        .await?
+        .take::<Vec<Record<Document>>>(0)?
+        .is_empty());
+
+    alice
+        .idempotency_key("key")
+        .create_one(document("c", "c"))
+        .await?;
+    let keys: Vec<RecordId> = surreal
+        .query("SELECT VALUE id FROM idempotency;")
+        .await?
+        .take(0)?;
+    assert_eq!(keys.len(), 1);
+    assert_eq!(
+        alice
+            .table_of::<Document>()
+            .select()?
+            .order_by("id")
+            .query::<Document>()
+            .await?,
+        vec![
+            Document {
+                id: Some("a".to_string()),
+                text: "changed".to_string(),
+            },
+            document("c", "c"),
+        ]
+    );
+
+    // an editor changes the content, but cannot take over the record
+    Client::signin(
+        surreal.clone(),
+        "test",
+        "test",
+        Credentials::record_signin(
+            "account",
+            Login {
+                name: "bob",
+                pass: "secret",
+            },
+        )?,
+    )
+    .await?;
+    surreal
+        .query("UPDATE documents:a SET owner = 'user:bob', readers = ['user:eve'], editors = ['user:bob', 'user:eve'], content.text = 'edited';")
+        .await?
+        .check()?;
+    let record: Vec<Record<Document>> =
+        surreal.query("SELECT * FROM documents:a;").await?.take(0)?;
+    assert_eq!(record[0].readers(), [] as [String; 0]);
+    assert_eq!(record[0].editors(), ["user:bob".to_string()]);
+    let owner: Option<String> = surreal
+        .query("SELECT VALUE owner FROM ONLY documents:a;")
+        .await?
+        .take(0)?;
+    assert_eq!(owner.as_deref(), Some("user:alice"));
+    assert_eq!(record.into_iter().next().unwrap().content()?.text, "edited");
+
+    Ok(())
+}
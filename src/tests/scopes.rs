@@ -0,0 +1,131 @@
+use super::*;
+use crate::Client;
+
+#[tokio::test]
+async fn read_and_write_scopes() -> Result<(), Error> {
+    let db = Client::memory("test", "test").await?;
+    db.owner("household")
+        .create_one(document("milk", "milk"))
+        .await?;
+    db.owner("alice").create_one(document("tea", "tea")).await?;
+    db.owner("bob").create_one(document("beer", "beer")).await?;
+
+    assert!(db
+        .table_of::<Document>()
+        .select()?
+        .query::<Document>()
+        .await?
+        .is_empty());
+    assert_eq!(db.table_of::<Document>().select()?.count().await?, 0);
+
+    let alice = db
+        .owners(vec!["alice".into(), "household".into()])
+        .write_owners(vec!["alice".into()]);
+    assert_eq!(
+        db.owner("alice")
+            .read_owners(vec!["household".into()])
+            .table_of::<Document>()
+            .select()?
+            .order_by("id")
+            .query::<Document>()
+            .await?,
+        vec![document("milk", "milk"), document("tea", "tea")]
+    );
+    assert_eq!(
+        alice
+            .table_of::<Document>()
+            .select()?
+            .order_by("id")
+            .query::<Document>()
+            .await?,
+        vec![document("milk", "milk"), document("tea", "tea")]
+    );
+
+    alice.update_one(document("tea", "green tea")).await?;
+    assert!(matches!(
+        alice.update_one(document("milk", "oat milk")).await,
+        Err(Error::Unauthorized { .. })
+    ));
+    assert!(matches!(
+        alice.delete_one(document("milk", "milk")).await,
+        Err(Error::Unauthorized { .. })
+    ));
+    assert!(matches!(
+        alice
+            .transaction(
+                |transaction| async move { transaction.delete_one(document("milk", "milk")) }
+            )
+            .await,
+        Err(Error::Unauthorized { .. })
+    ));
+
+    let changed = alice
+        .table_of::<Document>()
+        .change()?
+        .update("content.text", &ChangeOperator::Set, &"changed")?
+        .query::<Document>()
+        .await?;
+    assert_eq!(changed, vec![document("tea", "changed")]);
+
+    let reader = db.read_owners(vec!["household".into()]);
+    assert_eq!(
+        reader
+            .table_of::<Document>()
+            .select()?
+            .query::<Document>()
+            .await?,
+        vec![document("milk", "milk")]
+    );
+    assert!(reader
+        .table_of::<Document>()
+        .change()?
+        .update("content.text", &ChangeOperator::Set, &"changed")?
+        .query::<Document>()
+        .await?
+        .is_empty());
+    assert_eq!(
+        reader
+            .owner("alice")
+            .table_of::<Document>()
+            .select()?
+            .order_by("id")
+            .query::<Document>()
+            .await?,
+        vec![document("milk", "milk"), document("tea", "changed")]
+    );
+
+    // a client without owners creates no records
+    for client in [db.clone(), reader] {
+        assert!(matches!(
+            client.create_one(document("wine", "wine")).await,
+            Err(Error::Unauthorized { .. })
+        ));
+        assert!(matches!(
+            client.create(vec![document("wine", "wine")]).await,
+            Err(Error::Unauthorized { .. })
+        ));
+        assert!(matches!(
+            client.update_one(document("wine", "wine")).await,
+            Err(Error::Unauthorized { .. })
+        ));
+        assert!(matches!(
+            client
+                .transaction(|transaction| async move {
+                    transaction.create_one(document("wine", "wine"))
+                })
+                .await,
+            Err(Error::Unauthorized { .. })
+        ));
+    }
+    assert_eq!(
+        db.owner("alice")
+            .read_owners(vec!["household".into(), "bob".into()])
+            .table_of::<Document>()
+            .select()?
+            .count()
+            .await?,
+        3
+    );
+
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use super::*;
+use crate::{Client, Permission};
+
+#[tokio::test]
+async fn sharing() -> Result<(), Error> {
+    let db = Client::memory("test", "test").await?;
+    let alice = db.owner("alice");
+    let bob = db.owner("bob");
+    alice.create_one(entry("holiday", 100)).await?;
+
+    assert!(visible::<Entry>(&bob).await?.is_empty());
+    assert!(matches!(
+        bob.share::<Entry, _>("holiday", "bob", Permission::Edit)
+            .await,
+        Err(Error::Unauthorized { .. })
+    ));
+    assert!(matches!(
+        alice
+            .share::<Entry, _>("missing", "bob", Permission::Read)
+            .await,
+        Err(Error::NotFound { .. })
+    ));
+
+    alice
+        .share::<Entry, _>("holiday", "bob", Permission::Read)
+        .await?;
+    assert_eq!(visible::<Entry>(&bob).await?, vec![entry("holiday", 100)]);
+    assert!(matches!(
+        bob.update_one(entry("holiday", 200)).await,
+        Err(Error::Unauthorized { .. })
+    ));
+    assert!(bob
+        .table_of::<Entry>()
+        .change()?
+        .update("content.amount", &ChangeOperator::Set, &200)?
+        .query::<Entry>()
+        .await?
+        .is_empty());
+
+    alice
+        .share::<Entry, _>("holiday", "bob", Permission::Edit)
+        .await?;
+    bob.table_of::<Entry>()
+        .change()?
+        .id("holiday")
+        .update("content.amount", &ChangeOperator::Set, &300)?
+        .query::<Entry>()
+        .await?;
+    bob.transaction(|transaction| async move { transaction.update_one(entry("holiday", 400)) })
+        .await?;
+    assert_eq!(visible::<Entry>(&alice).await?, vec![entry("holiday", 400)]);
+
+    // editors may change the content, but neither delete nor share it
+    assert!(matches!(
+        bob.delete_one(entry("holiday", 400)).await,
+        Err(Error::Unauthorized { .. })
+    ));
+    assert!(matches!(
+        bob.soft_delete().delete_one(entry("holiday", 400)).await,
+        Err(Error::Unauthorized { .. })
+    ));
+    assert!(matches!(
+        bob.purge_one(entry("holiday", 400)).await,
+        Err(Error::Unauthorized { .. })
+    ));
+    assert!(matches!(
+        bob.transaction(|transaction| async move { transaction.delete_one(entry("holiday", 400)) })
+            .await,
+        Err(Error::Unauthorized { .. })
+    ));
+    assert!(matches!(
+        bob.share::<Entry, _>("holiday", "carol", Permission::Read)
+            .await,
+        Err(Error::Unauthorized { .. })
+    ));
+    assert!(matches!(
+        bob.unshare::<Entry, _>("holiday", "bob").await,
+        Err(Error::Unauthorized { .. })
+    ));
+    for (field, operator, value) in [
+        ("owner", ChangeOperator::Set, "bob"),
+        ("readers", ChangeOperator::Add, "carol"),
+        ("editors", ChangeOperator::Remove, "bob"),
+    ] {
+        assert!(matches!(
+            bob.table_of::<Entry>()
+                .change()?
+                .id("holiday")
+                .update(field, &operator, &value),
+            Err(Error::Validation(_))
+        ));
+    }
+    assert_eq!(visible::<Entry>(&alice).await?, vec![entry("holiday", 400)]);
+
+    let record = alice
+        .table_of::<Entry>()
+        .select()?
+        .query_direct_one::<Record<Entry>>()
+        .await?;
+    assert_eq!(record.readers(), ["bob".to_string()]);
+    assert_eq!(record.editors(), ["bob".to_string()]);
+
+    alice.unshare::<Entry, _>("holiday", "bob").await?;
+    assert!(visible::<Entry>(&bob).await?.is_empty());
+    assert!(matches!(
+        bob.update_one(entry("holiday", 500)).await,
+        Err(Error::Unauthorized { .. })
+    ));
+
+    Ok(())
+}
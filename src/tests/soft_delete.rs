@@ -0,0 +1,95 @@
+use super::*;
+use crate::Client;
+
+#[tokio::test]
+async fn soft_delete() -> Result<(), Error> {
+    let db = Client::memory("test", "test")
+        .await?
+        .owner("alice")
+        .soft_delete();
+
+    db.create(vec![entry("a", 1), entry("b", 2)]).await?;
+    db.delete_one(entry("a", 1)).await?;
+
+    let select = db.table_of::<Entry>().select()?;
+    assert_eq!(select.query::<Entry>().await?, vec![entry("b", 2)]);
+    assert_eq!(select.query_page::<Entry>().await?.total, 1);
+
+    let deleted = select
+        .clone()
+        .with_deleted()
+        .id("a")
+        .query_direct_one::<Record<Entry>>()
+        .await?;
+    assert!(deleted.deleted_at().is_some());
+    assert_eq!(deleted.content()?, entry("a", 1));
+
+    assert!(db
+        .table_of::<Entry>()
+        .change()?
+        .id("a")
+        .update("content.amount", &ChangeOperator::Set, &5)?
+        .query::<Entry>()
+        .await?
+        .is_empty());
+    assert_eq!(
+        db.table_of::<Entry>()
+            .change()?
+            .with_deleted()
+            .id("a")
+            .update("content.amount", &ChangeOperator::Set, &1)?
+            .query::<Entry>()
+            .await?,
+        vec![entry("a", 1)]
+    );
+
+    let other = db.owner("bob");
+    assert!(matches!(
+        other.restore_one(entry("a", 1)).await,
+        Err(Error::Unauthorized { .. })
+    ));
+    assert!(matches!(
+        other.purge_one(entry("a", 1)).await,
+        Err(Error::Unauthorized { .. })
+    ));
+
+    db.restore_one(entry("a", 1)).await?;
+    assert_eq!(
+        db.table_of::<Entry>()
+            .select()?
+            .order_by("id")
+            .query::<Entry>()
+            .await?,
+        vec![entry("a", 1), entry("b", 2)]
+    );
+
+    db.purge_one(entry("b", 2)).await?;
+    assert_eq!(
+        db.table_of::<Entry>()
+            .select()?
+            .with_deleted()
+            .query::<Entry>()
+            .await?,
+        vec![entry("a", 1)]
+    );
+
+    db.transaction(|transaction| async move { transaction.delete_one(entry("a", 1)) })
+        .await?;
+    assert!(db
+        .table_of::<Entry>()
+        .select()?
+        .query::<Entry>()
+        .await?
+        .is_empty());
+    assert_eq!(
+        db.table_of::<Entry>()
+            .select()?
+            .with_deleted()
+            .query::<Entry>()
+            .await?
+            .len(),
+        1
+    );
+
+    Ok(())
+}
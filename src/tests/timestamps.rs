@@ -0,0 +1,70 @@
+use super::*;
+use crate::{Client, Direction};
+use chrono::Utc;
+
+#[tokio::test]
+async fn timestamps() -> Result<(), Error> {
+    let db = Client::memory("test", "test").await?.owner("test");
+
+    let before = Utc::now();
+    db.create_one(document("first", "a")).await?;
+    let created = record::<Document>(&db, "first").await?;
+    let created_at = created.created_at().unwrap();
+    assert!(created_at >= before);
+    assert_eq!(created.updated_at(), Some(created_at));
+
+    db.update_one(document("first", "b")).await?;
+    let updated = record::<Document>(&db, "first").await?;
+    assert_eq!(updated.created_at(), Some(created_at));
+    assert!(updated.updated_at().unwrap() > created_at);
+
+    let changed_before = updated.updated_at().unwrap();
+    db.table_of::<Document>()
+        .change()?
+        .id("first")
+        .update("content.text", &ChangeOperator::Set, &"c")?
+        .query::<Document>()
+        .await?;
+    let changed = record::<Document>(&db, "first").await?;
+    assert_eq!(changed.created_at(), Some(created_at));
+    assert!(changed.updated_at().unwrap() > changed_before);
+
+    db.update_one(document("second", "d")).await?;
+    let upserted = record::<Document>(&db, "second").await?;
+    assert!(upserted.created_at().unwrap() > created_at);
+
+    let notes = db
+        .table_of::<Document>()
+        .select()?
+        .created_after(created_at)
+        .query::<Document>()
+        .await?;
+    assert_eq!(notes, vec![document("second", "d")]);
+
+    let notes = db
+        .table_of::<Document>()
+        .select()?
+        .order_by_created(Direction::Desc)
+        .query::<Document>()
+        .await?;
+    assert_eq!(notes, vec![document("second", "d"), document("first", "c")]);
+
+    let notes = db
+        .table_of::<Document>()
+        .select()?
+        .updated_before(upserted.updated_at().unwrap())
+        .query::<Document>()
+        .await?;
+    assert_eq!(notes, vec![document("first", "c")]);
+
+    db.create(vec![document("third", "e")]).await?;
+    db.transaction(|tx| async move { tx.create_one(document("fourth", "f")) })
+        .await?;
+    for id in ["third", "fourth"] {
+        let created = record::<Document>(&db, id).await?;
+        assert!(created.created_at().unwrap() > upserted.created_at().unwrap());
+        assert_eq!(created.updated_at(), created.created_at());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use super::SimpleDatabasable;
+use crate::{Client, Error, Principal};
+
+fn simple(id: &str) -> SimpleDatabasable {
+    SimpleDatabasable {
+        id: Some(id.into()),
+    }
+}
+
+#[tokio::test]
+async fn transaction() -> Result<(), Error> {
+    let db = Client::memory("test", "test").await?;
+    let db = db.table("transaction");
+
+    db.owner("owner_b").create_one(simple("b")).await?;
+
+    db.owner("owner_a")
+        .transaction(|tx| async move {
+            tx.create(vec![simple("a1"), simple("a2")])?;
+            tx.update_one(simple("a1"))?;
+            tx.delete_one(simple("a2"))
+        })
+        .await?;
+    assert_eq!(
+        db.owner("owner_a")
+            .select()?
+            .query::<SimpleDatabasable>()
+            .await?,
+        vec![simple("a1")]
+    );
+
+    assert!(db
+        .owner("owner_a")
+        .transaction(|tx| async move {
+            tx.create_one(simple("a3"))?;
+            Err::<(), Error>(Error::new("abort"))
+        })
+        .await
+        .is_err());
+
+    assert!(db
+        .owner("owner_a")
+        .transaction(|tx| async move {
+            tx.create_one(simple("a4"))?;
+            tx.delete_one(simple("b"))
+        })
+        .await
+        .is_err_and(|err| matches!(
+            err,
+            Error::Unauthorized { table, id } if table == "transaction" && id == "b"
+        )));
+
+    assert_eq!(
+        db.principal(Principal::Admin)
+            .select()?
+            .order_by("id")
+            .query::<SimpleDatabasable>()
+            .await?,
+        vec![simple("a1"), simple("b")]
+    );
+
+    Ok(())
+}
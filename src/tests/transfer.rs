@@ -0,0 +1,82 @@
+use super::*;
+use crate::{Client, Permission};
+
+#[tokio::test]
+async fn transfer_owner() -> Result<(), Error> {
+    let db = Client::memory("test", "test").await?.history();
+    let alice = db.owner("alice");
+    let bob = db.owner("bob");
+    alice
+        .create(vec![document("a", "a"), document("b", "b")])
+        .await?;
+    bob.create_one(document("c", "c")).await?;
+
+    assert!(matches!(
+        alice
+            .transfer_owner::<Document, _>(vec!["a", "c"], "alice")
+            .await,
+        Err(Error::Unauthorized { .. })
+    ));
+    assert!(matches!(
+        alice
+            .transfer_owner::<Document, _>(vec!["a", "missing"], "bob")
+            .await,
+        Err(Error::NotFound { .. })
+    ));
+    assert_eq!(
+        visible::<Document>(&alice).await?,
+        vec![document("a", "a"), document("b", "b")]
+    );
+
+    alice
+        .share::<Document, _>("a", "carol", Permission::Edit)
+        .await?;
+    let carol = db.owner("carol");
+    assert!(matches!(
+        carol
+            .transfer_owner::<Document, _>(vec!["a"], "carol")
+            .await,
+        Err(Error::Unauthorized { .. })
+    ));
+    assert!(matches!(
+        carol
+            .transaction(|transaction| async move {
+                transaction.transfer_owner::<Document, _>(vec!["a"], "carol")
+            })
+            .await,
+        Err(Error::Unauthorized { .. })
+    ));
+
+    alice
+        .transfer_owner::<Document, _>(vec!["a", "b"], "bob")
+        .await?;
+    assert!(visible::<Document>(&alice).await?.is_empty());
+    assert_eq!(
+        visible::<Document>(&bob).await?,
+        vec![document("a", "a"), document("b", "b"), document("c", "c")]
+    );
+    assert!(visible::<Document>(&carol).await?.is_empty());
+
+    // the owner check runs in the transaction of the write it guards
+    assert!(matches!(
+        alice
+            .idempotency_key("late")
+            .update_one(document("a", "a"))
+            .await,
+        Err(Error::Unauthorized { .. })
+    ));
+    assert!(db
+        .surreal()
+        .query("SELECT VALUE id FROM idempotency;")
+        .await?
+        .take::<Vec<RecordId>>(0)?
+        .is_empty());
+
+    let revisions = bob.revisions::<Document, _>("a").await?;
+    assert!(revisions.is_empty());
+    let revisions = alice.revisions::<Document, _>("a").await?;
+    assert_eq!(revisions.len(), 1);
+    assert_eq!(revisions[0].owner(), Some("alice"));
+
+    Ok(())
+}
@@ -0,0 +1,125 @@
+use crate::{Client, Databasable, Error, Ulid};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::{Array, Value};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Databasable)]
+struct Counter {
+    id: Option<i64>,
+    value: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Databasable)]
+struct NamedCounter {
+    id: Option<String>,
+    value: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Databasable)]
+struct Monthly {
+    id: Option<Array>,
+    total: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Databasable)]
+struct Event {
+    id: Option<Ulid>,
+    name: String,
+}
+
+#[tokio::test]
+async fn typed_id() -> Result<(), Error> {
+    let db = Client::memory("test", "test").await?;
+
+    let counter = Counter {
+        id: Some(7),
+        value: 1,
+    };
+    db.table("counter")
+        .owner("owner")
+        .create_one(counter.clone())
+        .await?;
+    db.table("counter")
+        .owner("owner")
+        .update_one(Counter {
+            id: Some(7),
+            value: 2,
+        })
+        .await?;
+    assert_eq!(
+        db.table("counter")
+            .owner("owner")
+            .select()?
+            .id(7)
+            .query_one::<Counter>()
+            .await?,
+        Counter {
+            id: Some(7),
+            value: 2,
+        }
+    );
+    assert!(matches!(
+        db.table("counter").owner("other").delete_one(counter).await,
+        Err(Error::Unauthorized { id, .. }) if id == "7"
+    ));
+
+    let key = Array::from(vec![Value::from("alice"), Value::from(2024)]);
+    let monthly = Monthly {
+        id: Some(key.clone()),
+        total: 42,
+    };
+    db.table("monthly")
+        .owner("owner")
+        .create(vec![monthly.clone()])
+        .await?;
+    assert_eq!(
+        db.table("monthly")
+            .owner("owner")
+            .select()?
+            .id(key)
+            .query::<Monthly>()
+            .await?,
+        vec![monthly]
+    );
+
+    let created = db
+        .table("event")
+        .owner("owner")
+        .create_one(Event {
+            id: None,
+            name: "launch".into(),
+        })
+        .await?;
+    let id = created[0].id.expect("generated ulid");
+    assert_eq!(
+        db.table("event")
+            .owner("owner")
+            .select()?
+            .id(id.to_string())
+            .query_one::<Event>()
+            .await?,
+        created[0]
+    );
+
+    db.table("counter")
+        .owner("owner")
+        .create_one(NamedCounter {
+            id: Some("text".into()),
+            value: 3,
+        })
+        .await?;
+    assert!(matches!(
+        db.table("counter").owner("owner").select()?.query::<Counter>().await,
+        Err(Error::IdMismatch { table, id }) if table == "counter" && id == "text"
+    ));
+    assert!(matches!(
+        db.table("counter")
+            .owner("owner")
+            .select()?
+            .id(7)
+            .query::<NamedCounter>()
+            .await,
+        Err(Error::IdMismatch { table, id }) if table == "counter" && id == "7"
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use super::*;
+use crate::Client;
+
+#[tokio::test]
+async fn versions() -> Result<(), Error> {
+    let db = Client::memory("test", "test").await?.owner("test");
+
+    db.create_one(document("doc", "a")).await?;
+    assert_eq!(record::<Document>(&db, "doc").await?.version(), Some(1));
+
+    db.update_one(document("doc", "b")).await?;
+    assert_eq!(record::<Document>(&db, "doc").await?.version(), Some(2));
+
+    assert_eq!(
+        db.update_one_if_version(document("doc", "c"), 2).await?,
+        vec![document("doc", "c")]
+    );
+    assert_eq!(record::<Document>(&db, "doc").await?.version(), Some(3));
+
+    let stale = db.update_one_if_version(document("doc", "d"), 2).await;
+    assert!(matches!(stale, Err(Error::Conflict { .. })));
+    assert_eq!(
+        db.table_of::<Document>()
+            .select()?
+            .query_one::<Document>()
+            .await?,
+        document("doc", "c")
+    );
+
+    db.table_of::<Document>()
+        .change()?
+        .id("doc")
+        .update("content.text", &ChangeOperator::Set, &"e")?
+        .query::<Document>()
+        .await?;
+    assert_eq!(record::<Document>(&db, "doc").await?.version(), Some(4));
+    for field in ["version", "created_at", "updated_at", "id"] {
+        assert!(matches!(
+            db.table_of::<Document>()
+                .change()?
+                .update(field, &ChangeOperator::Set, &0),
+            Err(Error::Validation(_))
+        ));
+    }
+
+    let missing = db
+        .update_one_if_version(
+            Document {
+                id: Some("missing".to_string()),
+                text: "f".to_string(),
+            },
+            1,
+        )
+        .await;
+    assert!(matches!(missing, Err(Error::NotFound { .. })));
+
+    Ok(())
+}
@@ -1,4 +1,129 @@
+use surrealdb::sql::{Array, Id, Object, Uuid};
+use ulid::Ulid;
+
 pub trait Databasable {
-    fn get_id(&self) -> Option<String>;
-    fn set_id(&mut self, id: Option<String>);
+    type Id: RecordKey;
+
+    fn get_id(&self) -> Option<Self::Id>;
+    fn set_id(&mut self, id: Option<Self::Id>);
+
+    fn table() -> Option<&'static str> {
+        None
+    }
+}
+
+pub trait RecordKey: Sized {
+    fn into_id(self) -> Id;
+    fn from_id(id: Id) -> Option<Self>;
+
+    /// Key for records created without one, `None` lets the database pick a
+    /// random string.
+    fn generate() -> Option<Self> {
+        None
+    }
+}
+
+/// Gives `content` a generated key if it has none.
+pub(crate) fn with_key<T: Databasable>(mut content: T) -> T {
+    if content.get_id().is_none() {
+        content.set_id(T::Id::generate());
+    }
+    content
+}
+
+impl RecordKey for Id {
+    fn into_id(self) -> Id {
+        self
+    }
+
+    fn from_id(id: Id) -> Option<Self> {
+        Some(id)
+    }
+}
+
+impl RecordKey for String {
+    fn into_id(self) -> Id {
+        Id::String(self)
+    }
+
+    fn from_id(id: Id) -> Option<Self> {
+        match id {
+            Id::String(id) => Some(id),
+            _ => None,
+        }
+    }
+}
+
+impl RecordKey for i64 {
+    fn into_id(self) -> Id {
+        Id::Number(self)
+    }
+
+    fn from_id(id: Id) -> Option<Self> {
+        match id {
+            Id::Number(id) => Some(id),
+            _ => None,
+        }
+    }
+}
+
+impl RecordKey for Uuid {
+    fn into_id(self) -> Id {
+        Id::Uuid(self)
+    }
+
+    fn from_id(id: Id) -> Option<Self> {
+        match id {
+            Id::Uuid(id) => Some(id),
+            _ => None,
+        }
+    }
+
+    fn generate() -> Option<Self> {
+        Some(Uuid::new_v7())
+    }
+}
+
+/// Stored as string key, like the keys of SurrealDB's `ulid()`.
+impl RecordKey for Ulid {
+    fn into_id(self) -> Id {
+        Id::String(self.to_string())
+    }
+
+    fn from_id(id: Id) -> Option<Self> {
+        match id {
+            Id::String(id) => id.parse().ok(),
+            _ => None,
+        }
+    }
+
+    fn generate() -> Option<Self> {
+        Some(Ulid::new())
+    }
+}
+
+impl RecordKey for Array {
+    fn into_id(self) -> Id {
+        Id::Array(self)
+    }
+
+    fn from_id(id: Id) -> Option<Self> {
+        match id {
+            Id::Array(id) => Some(id),
+            _ => None,
+        }
+    }
+}
+
+impl RecordKey for Object {
+    fn into_id(self) -> Id {
+        Id::Object(self)
+    }
+
+    fn from_id(id: Id) -> Option<Self> {
+        match id {
+            Id::Object(id) => Some(id),
+            _ => None,
+        }
+    }
 }
@@ -0,0 +1,338 @@
+use crate::error::aborted;
+use crate::hierarchy::owner_condition;
+use crate::traits::with_key;
+use crate::{Bindings, Client, Databasable, Error, Principal, Record, RecordKey};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use surrealdb::error::Db;
+use surrealdb::sql::{Id, Thing};
+
+/// Condition a guard statement of the queue throws on, reported by
+/// `commit` as the matching error.
+#[derive(Debug, Clone)]
+pub(crate) enum Guard {
+    Unauthorized { table: String, id: String },
+    NotFound { table: String, id: String },
+}
+
+impl From<Guard> for Error {
+    fn from(guard: Guard) -> Self {
+        match guard {
+            Guard::Unauthorized { table, id } => Self::Unauthorized { table, id },
+            Guard::NotFound { table, id } => Self::NotFound {
+                table,
+                id: Some(id),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Queue {
+    statements: Vec<String>,
+    guards: HashMap<usize, Guard>,
+    bindings: Bindings,
+}
+
+impl Queue {
+    fn statement(&mut self, statement: String) {
+        if !statement.is_empty() {
+            self.statements.push(statement);
+        }
+    }
+
+    /// Queues `statement`, which throws if `guard` applies.
+    fn guard(&mut self, statement: String, guard: Guard) {
+        self.guards.insert(self.statements.len(), guard);
+        self.statements.push(statement);
+    }
+}
+
+/// Collects statements of `Client::transaction` which are sent as a single
+/// `BEGIN TRANSACTION ... COMMIT TRANSACTION` block once the closure succeeded.
+#[derive(Debug, Clone)]
+pub struct Transaction<'a> {
+    client: Client<'a>,
+    queue: Arc<Mutex<Queue>>,
+}
+
+impl<'a> Transaction<'a> {
+    pub(crate) fn new(client: Client<'a>) -> Self {
+        Self {
+            client,
+            queue: Arc::new(Mutex::new(Queue::default())),
+        }
+    }
+
+    pub fn table(&self, table: &'a str) -> Self {
+        Self {
+            client: self.client.table(table),
+            queue: self.queue.clone(),
+        }
+    }
+
+    pub fn owner(&self, owner: &'a str) -> Self {
+        Self {
+            client: self.client.owner(owner),
+            queue: self.queue.clone(),
+        }
+    }
+
+    pub fn owners(&self, owners: Vec<String>) -> Self {
+        Self {
+            client: self.client.owners(owners),
+            queue: self.queue.clone(),
+        }
+    }
+
+    pub fn write_owners(&self, owners: Vec<String>) -> Self {
+        Self {
+            client: self.client.write_owners(owners),
+            queue: self.queue.clone(),
+        }
+    }
+
+    pub fn principal(&self, principal: Principal) -> Self {
+        Self {
+            client: self.client.principal(principal),
+            queue: self.queue.clone(),
+        }
+    }
+
+    fn get_table_for<T: Databasable>(&self) -> Result<&'a str, Error> {
+        self.client.get_table_for::<T>()
+    }
+
+    fn first_owner(&self) -> Option<String> {
+        self.client.first_owner()
+    }
+
+    /// Queues the statements of `f`, or none of them if it fails.
+    fn push<F>(&self, f: F) -> Result<(), Error>
+    where
+        F: FnOnce(&mut Queue) -> Result<(), Error>,
+    {
+        let mut queue = self.queue.lock().map_err(|_| Error::PoisonedTransaction)?;
+        let len = queue.statements.len();
+        let result = f(&mut queue);
+        if result.is_err() {
+            queue.statements.truncate(len);
+            queue.guards.retain(|index, _| *index < len);
+        }
+        result
+    }
+
+    fn history_str(&self, bindings: &mut Bindings, table: &str, source: &str) -> String {
+        self.client.history_str(bindings, table, source)
+    }
+
+    /// Queues the check that the client owns the record `id` or is listed in
+    /// one of its access lists `shared` and returns the parameter holding
+    /// the record.
+    fn authorization(
+        &self,
+        queue: &mut Queue,
+        table: &str,
+        id: Id,
+        shared: &[&str],
+    ) -> Result<String, Error> {
+        self.client.writable(table, Some(&id))?;
+        let guard = Guard::Unauthorized {
+            table: table.to_string(),
+            id: id.to_raw(),
+        };
+        let record = queue.bindings.bind("id", Thing::from((table, id)));
+        let allowed = match self.client.write_scope() {
+            Some(owners) => owner_condition(
+                &mut queue.bindings,
+                owners.to_vec(),
+                shared,
+                self.client.hierarchical(),
+            ),
+            None => return Ok(record),
+        };
+        queue.guard(authorization_str(&record, &allowed), guard);
+        Ok(record)
+    }
+
+    pub fn create_one<T: Databasable + Serialize + DeserializeOwned + 'static>(
+        &self,
+        content: T,
+    ) -> Result<(), Error> {
+        let table = self.get_table_for::<T>()?;
+        let content = with_key(content);
+        self.client
+            .writable(table, content.get_id().map(RecordKey::into_id).as_ref())?;
+        self.push(|queue| {
+            let target = match content.get_id() {
+                Some(id) => queue
+                    .bindings
+                    .bind("id", Thing::from((table, id.into_id()))),
+                None => table.to_string(),
+            };
+            let create = Record::new(content, table.to_string(), self.first_owner())
+                .create_str(&mut queue.bindings, &target)?;
+            queue.statement(create);
+            Ok(())
+        })
+    }
+
+    pub fn create<T: Databasable + Serialize + DeserializeOwned + 'static>(
+        &self,
+        content: Vec<T>,
+    ) -> Result<(), Error> {
+        content
+            .into_iter()
+            .try_for_each(|content| self.create_one(content))
+    }
+
+    pub fn update_one<T: Databasable + Serialize + DeserializeOwned + 'static>(
+        &self,
+        content: T,
+    ) -> Result<(), Error> {
+        let table = self.get_table_for::<T>()?;
+        let id = content
+            .get_id()
+            .ok_or(Error::MissingId {
+                table: table.to_string(),
+            })?
+            .into_id();
+        self.push(|queue| {
+            let id = self.authorization(queue, table, id, &["editors"])?;
+            let history = self.history_str(&mut queue.bindings, table, &id);
+            queue.statement(history);
+            let upsert = Record::new(content, table.to_string(), self.first_owner())
+                .upsert_str(&mut queue.bindings, &id)?;
+            queue.statement(upsert);
+            Ok(())
+        })
+    }
+
+    pub fn update<T: Databasable + Serialize + DeserializeOwned + 'static>(
+        &self,
+        content: Vec<T>,
+    ) -> Result<(), Error> {
+        content
+            .into_iter()
+            .try_for_each(|content| self.update_one(content))
+    }
+
+    pub fn delete_one<T: Databasable + Serialize + DeserializeOwned + 'static>(
+        &self,
+        content: T,
+    ) -> Result<(), Error> {
+        let table = self.get_table_for::<T>()?;
+        let id = content
+            .get_id()
+            .ok_or(Error::MissingId {
+                table: table.to_string(),
+            })?
+            .into_id();
+        self.push(|queue| {
+            let id = self.authorization(queue, table, id, &[])?;
+            let history = self.history_str(&mut queue.bindings, table, &id);
+            queue.statement(history);
+            queue.statement(if self.client.soft_deletes() {
+                format!(
+                    "UPDATE {} SET deleted_at = time::now(), updated_at = time::now(), version = (version ?? 0) + 1;",
+                    id
+                )
+            } else {
+                format!("DELETE {};", id)
+            });
+            Ok(())
+        })
+    }
+
+    pub fn delete<T: Databasable + Serialize + DeserializeOwned + 'static>(
+        &self,
+        content: Vec<T>,
+    ) -> Result<(), Error> {
+        content
+            .into_iter()
+            .try_for_each(|content| self.delete_one(content))
+    }
+
+    /// Hands the records `ids` over to `new_owner` and clears their readers
+    /// and editors, failing the whole transaction if one of them is missing
+    /// or not owned by the client.
+    pub fn transfer_owner<T: Databasable, K: Into<Id>>(
+        &self,
+        ids: Vec<K>,
+        new_owner: &str,
+    ) -> Result<(), Error> {
+        let table = self.get_table_for::<T>()?;
+        ids.into_iter().try_for_each(|id| {
+            let id = id.into();
+            self.push(|queue| {
+                let not_found = Guard::NotFound {
+                    table: table.to_string(),
+                    id: id.to_raw(),
+                };
+                let id = self.authorization(queue, table, id, &[])?;
+                queue.guard(
+                    format!(
+                        "IF count(SELECT id FROM {}) == 0 {{ THROW 'not found' }};",
+                        id
+                    ),
+                    not_found,
+                );
+                let history = self.history_str(&mut queue.bindings, table, &id);
+                queue.statement(history);
+                let owner = queue.bindings.bind("new_owner", new_owner.to_string());
+                queue.statement(format!(
+                    "UPDATE {} SET owner = {}, readers = [], editors = [], updated_at = time::now(), version = (version ?? 0) + 1;",
+                    id, owner
+                ));
+                Ok(())
+            })
+        })
+    }
+
+    pub fn query_str(&self) -> Result<String, Error> {
+        let queue = self.queue.lock().map_err(|_| Error::PoisonedTransaction)?;
+        Ok(self.query_str_of(&queue))
+    }
+
+    fn query_str_of(&self, queue: &Queue) -> String {
+        format!(
+            "BEGIN TRANSACTION;\n{}\nCOMMIT TRANSACTION;",
+            queue.statements.join("\n")
+        )
+    }
+
+    pub(crate) async fn commit(self) -> Result<(), Error> {
+        let (query, bindings) = {
+            let queue = self.queue.lock().map_err(|_| Error::PoisonedTransaction)?;
+            if queue.statements.is_empty() {
+                return Ok(());
+            }
+            (self.query_str_of(&queue), queue.bindings.clone())
+        };
+        let mut response = self.client.surreal().query(query).bind(bindings).await?;
+        match aborted(&mut response) {
+            Some((index, err @ surrealdb::Error::Db(Db::Thrown(_)))) => {
+                let mut queue = self.queue.lock().map_err(|_| Error::PoisonedTransaction)?;
+                Err(match queue.guards.remove(&index) {
+                    Some(guard) => guard.into(),
+                    None => err.into(),
+                })
+            }
+            Some((_, err)) => Err(err.into()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Statement throwing if the record `record` exists but does not match
+/// `allowed`.
+pub(crate) fn authorization_str(record: &str, allowed: &str) -> String {
+    format!(
+        "IF count(SELECT id FROM {record}) == 1 AND count(SELECT id FROM {record} WHERE {allowed}) == 0 {{ THROW 'not authorized' }};",
+        record = record,
+        allowed = allowed,
+    )
+}